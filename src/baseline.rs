@@ -0,0 +1,215 @@
+use std::{
+    collections::BTreeMap,
+    path::{Component, Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::report::{Diagnostic, Rule};
+
+/// Problems that are known and accepted for now, so that only new problems fail a check.
+///
+/// Problems are recorded by rule, key and file, without a line, so that moving code around does
+/// not make them new. A problem found more often than recorded is new.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    #[serde(skip)]
+    path: PathBuf,
+    problems: Vec<BaselineEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    pub rule: Rule,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Relative to the baseline file, with `/` as separator
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// How many times the problem was found
+    pub count: usize,
+}
+
+#[derive(Error, Debug)]
+pub enum BaselineError {
+    #[error("could not read {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid baseline {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("could not write {path}: {source}")]
+    Write {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+type ProblemId = (Rule, Option<String>, Option<String>);
+
+impl Baseline {
+    /// A baseline of `diagnostics`, to be written to `path`.
+    pub fn new(path: &Path, diagnostics: &[Diagnostic]) -> Self {
+        let mut baseline = Self {
+            path: path.to_path_buf(),
+            problems: Vec::new(),
+        };
+        let mut counts = BTreeMap::<ProblemId, usize>::new();
+        for diagnostic in diagnostics {
+            *counts.entry(baseline.problem_id(diagnostic)).or_default() += 1;
+        }
+        baseline.problems = counts
+            .into_iter()
+            .map(|((rule, key, file), count)| BaselineEntry {
+                rule,
+                key,
+                file,
+                count,
+            })
+            .collect();
+        baseline
+    }
+
+    /// Load the baseline at `path`, a baseline that does not exist yet is empty.
+    pub fn load(path: &Path) -> Result<Self, BaselineError> {
+        let mut baseline = match std::fs::read_to_string(path) {
+            Ok(content) => {
+                serde_json::from_str::<Self>(&content).map_err(|source| BaselineError::Parse {
+                    path: path.to_path_buf(),
+                    source,
+                })?
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(source) => {
+                return Err(BaselineError::Read {
+                    path: path.to_path_buf(),
+                    source,
+                })
+            }
+        };
+        baseline.path = path.to_path_buf();
+        Ok(baseline)
+    }
+
+    pub fn save(&self) -> Result<(), BaselineError> {
+        let write_error = |source| BaselineError::Write {
+            path: self.path.clone(),
+            source,
+        };
+        let mut content = serde_json::to_string_pretty(self)
+            .map_err(|err| write_error(std::io::Error::other(err)))?;
+        content.push('\n');
+        std::fs::write(&self.path, content).map_err(write_error)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of problems in the baseline.
+    pub fn count(&self) -> usize {
+        self.problems.iter().map(|entry| entry.count).sum()
+    }
+
+    /// The diagnostics that are not in the baseline, followed by a diagnostic for each problem
+    /// of the baseline that is found less often than recorded.
+    pub fn apply(&self, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        let mut remaining = self
+            .problems
+            .iter()
+            .map(|entry| {
+                let id = (entry.rule, entry.key.clone(), entry.file.clone());
+                (id, entry.count)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let mut new = diagnostics
+            .into_iter()
+            .filter(
+                |diagnostic| match remaining.get_mut(&self.problem_id(diagnostic)) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        false
+                    }
+                    _ => true,
+                },
+            )
+            .collect::<Vec<_>>();
+
+        new.extend(self.problems.iter().filter_map(|entry| {
+            let id = (entry.rule, entry.key.clone(), entry.file.clone());
+            let fixed = remaining[&id];
+            (fixed > 0).then(|| Diagnostic::stale_baseline(entry, fixed, &self.path))
+        }));
+        new
+    }
+
+    fn problem_id(&self, diagnostic: &Diagnostic) -> ProblemId {
+        let file = diagnostic.file.as_ref().map(|file| {
+            let base = normalize(self.path.parent().unwrap_or(Path::new("")));
+            let file = normalize(file);
+            let relative = file.strip_prefix(&base).unwrap_or(&file);
+            relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/")
+        });
+        (diagnostic.rule, diagnostic.key.clone(), file)
+    }
+}
+
+/// `path` without `.` components, so that `./en.json` and `en.json` are the same file.
+fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|component| *component != Component::CurDir)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let unused =
+            |key: &str, file: &str| Diagnostic::unused_key(key, "Value", PathBuf::from(file));
+        let baseline = Baseline::new(
+            Path::new("./ramilang-baseline.json"),
+            &[
+                unused("common.save", "./en.json"),
+                unused("common.save", "./en.json"),
+                unused("common.cancel", "./en.json"),
+                unused("common.ok", "./en.json"),
+            ],
+        );
+        assert_eq!(4, baseline.count());
+        assert_eq!(Some("en.json"), baseline.problems[0].file.as_deref());
+
+        let diagnostics = baseline.apply(vec![
+            unused("common.save", "en.json"),
+            unused("common.cancel", "en.json"),
+            unused("common.cancel", "en.json"),
+            unused("common.new", "en.json"),
+        ]);
+
+        let problems = diagnostics
+            .iter()
+            .map(|d| (d.rule, d.key.as_deref().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (Rule::UnusedKey, "common.cancel"),
+                (Rule::UnusedKey, "common.new"),
+                (Rule::StaleBaseline, "common.ok"),
+                (Rule::StaleBaseline, "common.save"),
+            ],
+            problems
+        );
+    }
+}
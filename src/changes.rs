@@ -0,0 +1,219 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::PathBuf,
+};
+
+use crate::{
+    config::{Config, SourceFilter},
+    git::{canonicalize, GitError, Repository},
+    locale::LocaleSet,
+    report::{Diagnostic, Rule},
+    translation_file::TranslationFile,
+    ts_file::TSFile,
+};
+
+/// What the changes of a branch or commit are compared to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeBase {
+    /// The commit that the current branch and this ref branched from, compared to the working
+    /// tree
+    Since(String),
+    /// `HEAD`, compared to the changes staged for commit
+    Staged,
+}
+
+/// The files and keys touched by a change, to only report the problems that it may have caused.
+#[derive(Debug, Clone, Default)]
+pub struct Changes {
+    /// Changed files, canonicalized
+    files: HashSet<PathBuf>,
+    /// Keys added or changed in any of the translation files
+    keys: HashSet<String>,
+    /// Keys removed from any of the translation files, whose usages and other locales may now
+    /// lack them
+    removed: HashSet<String>,
+    /// Keys used by the earlier version of a changed source file, which may be unused now
+    previously_used: HashSet<String>,
+}
+
+impl Changes {
+    pub fn find(
+        repository: &Repository,
+        base: &ChangeBase,
+        config: &Config,
+        source_filter: &SourceFilter,
+        locales: &LocaleSet,
+    ) -> Result<Self, GitError> {
+        let (revision, files) = match base {
+            ChangeBase::Since(since) => {
+                let merge_base = repository.merge_base(since)?;
+                let files = repository.changed_files(&merge_base)?;
+                (merge_base, files)
+            }
+            ChangeBase::Staged => ("HEAD".to_string(), repository.staged_files()?),
+        };
+        let mut changes = Self {
+            files: files.into_iter().collect(),
+            ..Self::default()
+        };
+
+        for locale in locales.iter() {
+            let path = &locale.file.path;
+            if !changes.files.contains(&canonicalize(path)) {
+                continue;
+            }
+            // The staged version of the file, unless it can not be read
+            let staged = match base {
+                ChangeBase::Staged => repository
+                    .show("", path)?
+                    .and_then(|content| TranslationFile::parse(path.clone(), &content).ok()),
+                ChangeBase::Since(_) => None,
+            };
+            let entries = &staged.as_ref().unwrap_or(&locale.file).entries;
+            // Every key is new if the file did not exist, or could not be read
+            let previous = repository
+                .show(&revision, path)?
+                .and_then(|content| TranslationFile::parse(path.clone(), &content).ok())
+                .map(|file| file.entries)
+                .unwrap_or_else(BTreeMap::new);
+
+            changes.keys.extend(
+                entries
+                    .iter()
+                    .filter(|(key, value)| previous.get(*key) != Some(*value))
+                    .map(|(key, _)| key.clone()),
+            );
+            changes.removed.extend(
+                previous
+                    .into_keys()
+                    .filter(|key| !entries.contains_key(key)),
+            );
+        }
+
+        let roots = config
+            .roots
+            .iter()
+            .map(|root| canonicalize(root))
+            .collect::<Vec<_>>();
+        for file in &changes.files {
+            let is_source = roots.iter().any(|root| {
+                file.strip_prefix(root)
+                    .is_ok_and(|relative| source_filter.contains(relative))
+            });
+            if !is_source {
+                continue;
+            }
+            let Some(source) = repository.show(&revision, file)? else {
+                continue;
+            };
+            let previous = TSFile {
+                source,
+                path: file.clone(),
+            };
+            changes.previously_used.extend(
+                previous
+                    .find_all_usages(&config.patterns)
+                    .into_iter()
+                    .filter(|usage| !usage.dynamic)
+                    .map(|usage| usage.key),
+            );
+        }
+
+        Ok(changes)
+    }
+
+    /// The diagnostics that the change may have caused. Problems of usages are kept if they are
+    /// in a changed file, and problems of the translation files if their key was added or
+    /// changed. Unused keys are also kept if a changed file used them before, and invalid and
+    /// missing keys and stale ignore entries if their key was removed.
+    pub fn filter(&self, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        diagnostics
+            .into_iter()
+            .filter(|diagnostic| self.contains(diagnostic))
+            .collect()
+    }
+
+    fn contains(&self, diagnostic: &Diagnostic) -> bool {
+        let key = diagnostic.key.as_ref();
+        let key_changed = key.is_some_and(|key| self.keys.contains(key));
+        let key_removed = key.is_some_and(|key| self.removed.contains(key));
+        let file_changed = diagnostic
+            .file
+            .as_ref()
+            .is_some_and(|file| self.files.contains(&canonicalize(file)));
+
+        match diagnostic.rule {
+            Rule::MissingKey => key_changed || key_removed,
+            Rule::InvalidKey => file_changed || key_removed,
+            Rule::DuplicateKey
+            | Rule::EmptyValue
+            | Rule::InvalidMessage
+            | Rule::PlaceholderMismatch
+            | Rule::Untranslated
+            | Rule::StaleTranslation => key_changed,
+            // An ignore entry becomes stale when the last key it matched is removed
+            Rule::StaleIgnore => file_changed || key_removed,
+            Rule::MissingValue | Rule::UnusedValue => file_changed,
+            Rule::UnusedKey | Rule::StaleBaseline => {
+                key_changed || key.is_some_and(|key| self.previously_used.contains(key))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::ts_file::KeyUsage;
+
+    #[test]
+    fn test_filter() {
+        let changed = canonicalize(Path::new("src/Changed.tsx"));
+        let changes = Changes {
+            files: HashSet::from([changed]),
+            keys: HashSet::from(["common.save".to_string()]),
+            removed: HashSet::from(["common.gone".to_string()]),
+            previously_used: HashSet::from(["common.removed".to_string()]),
+        };
+        let unused = |key: &str| Diagnostic::unused_key(key, "Value", PathBuf::from("en.json"));
+        let with_rule = |rule: Rule, key: &str| {
+            let mut diagnostic = unused(key);
+            diagnostic.rule = rule;
+            diagnostic
+        };
+
+        let diagnostics = changes.filter(vec![
+            Diagnostic::invalid_key(&KeyUsage::test("common.invalid", "src/Changed.tsx")),
+            Diagnostic::invalid_key(&KeyUsage::test("common.invalid", "./src/Other.tsx")),
+            Diagnostic::invalid_key(&KeyUsage::test("common.gone", "./src/Other.tsx")),
+            with_rule(Rule::EmptyValue, "common.save"),
+            with_rule(Rule::EmptyValue, "common.cancel"),
+            with_rule(Rule::MissingKey, "common.gone"),
+            with_rule(Rule::MissingKey, "common.cancel"),
+            with_rule(Rule::StaleIgnore, "common.gone"),
+            with_rule(Rule::StaleIgnore, "common.cancel"),
+            unused("common.save"),
+            unused("common.removed"),
+            unused("common.old"),
+        ]);
+
+        let kept = diagnostics
+            .iter()
+            .map(|d| (d.rule, d.key.as_deref().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (Rule::InvalidKey, "common.invalid"),
+                (Rule::InvalidKey, "common.gone"),
+                (Rule::EmptyValue, "common.save"),
+                (Rule::MissingKey, "common.gone"),
+                (Rule::StaleIgnore, "common.gone"),
+                (Rule::UnusedKey, "common.save"),
+                (Rule::UnusedKey, "common.removed"),
+            ],
+            kept
+        );
+    }
+}
@@ -0,0 +1,306 @@
+use crate::{
+    ignore::IgnoreList,
+    locale::LocaleSet,
+    lock::TranslationLock,
+    report::{translation_file_diagnostics, Diagnostic},
+    translation_file::Message,
+    usage_index::UsageIndex,
+};
+
+/// Run every check and collect all problems found, instead of stopping at the first failing
+/// one. All diagnostics are errors until a severity is applied to them.
+///
+/// `allowed` are the values that may be the same in every locale.
+pub fn run(
+    locales: &LocaleSet,
+    usages: &UsageIndex,
+    ignore: &IgnoreList,
+    allowed: &[String],
+    lock: &TranslationLock,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = translation_files(locales);
+    diagnostics.extend(untranslated_values(locales, allowed));
+    diagnostics.extend(stale_translations(locales, lock));
+    diagnostics.extend(invalid_usages(locales, usages));
+    diagnostics.extend(message_values(locales, usages));
+    diagnostics.extend(unused_keys(locales, usages, ignore));
+    diagnostics.extend(stale_ignores(locales, ignore));
+
+    diagnostics
+}
+
+/// Duplicate keys, missing keys, empty values, invalid messages and placeholders that differ
+/// from the reference in the translation files.
+pub fn translation_files(locales: &LocaleSet) -> Vec<Diagnostic> {
+    let mut errors = locales
+        .iter()
+        .filter_map(|locale| locale.file.find_duplicate_keys())
+        .collect::<Vec<_>>();
+    if let Err(compatibility_errors) = locales.is_compatible() {
+        errors.extend(compatibility_errors);
+    }
+
+    errors
+        .iter()
+        .flat_map(translation_file_diagnostics)
+        .collect()
+}
+
+/// Values of the other locales that are the same as in the reference locale, except `allowed`.
+pub fn untranslated_values(locales: &LocaleSet, allowed: &[String]) -> Vec<Diagnostic> {
+    let reference = &locales.reference().file;
+    locales
+        .others()
+        .flat_map(|locale| locale.file.find_untranslated_values(reference, allowed))
+        .flat_map(|error| translation_file_diagnostics(&error))
+        .collect()
+}
+
+/// Values translated from a value of the reference locale that has changed since.
+pub fn stale_translations(locales: &LocaleSet, lock: &TranslationLock) -> Vec<Diagnostic> {
+    lock.find_stale_translations(locales)
+        .iter()
+        .flat_map(translation_file_diagnostics)
+        .collect()
+}
+
+/// Usages of keys that do not exist in the reference locale.
+pub fn invalid_usages(locales: &LocaleSet, usages: &UsageIndex) -> Vec<Diagnostic> {
+    let entries = &locales.reference().file.entries;
+    usages
+        .iter()
+        .filter(|usage| {
+            if usage.dynamic {
+                !entries.keys().any(|key| usage.matches(key))
+            } else {
+                !entries.contains_key(usage.key.as_str())
+            }
+        })
+        .map(Diagnostic::invalid_key)
+        .collect()
+}
+
+/// Values passed along with a key that do not match the arguments of its message in the
+/// reference locale. Tags are allowed but not required to have a value, since react-intl can
+/// get them from `defaultRichTextElements`.
+pub fn message_values(locales: &LocaleSet, usages: &UsageIndex) -> Vec<Diagnostic> {
+    let entries = &locales.reference().file.entries;
+    let mut diagnostics = Vec::new();
+    for usage in usages.iter().filter(|usage| !usage.dynamic) {
+        let Some(values) = &usage.values else {
+            continue;
+        };
+        // Invalid keys and messages are reported by their own checks
+        let Some(Ok(message)) = entries.get(&usage.key).map(|value| Message::parse(value)) else {
+            continue;
+        };
+        let placeholders = message.placeholders();
+
+        diagnostics.extend(
+            placeholders
+                .arguments
+                .keys()
+                .filter(|argument| !values.contains(argument))
+                .map(|argument| Diagnostic::missing_value(usage, argument)),
+        );
+        diagnostics.extend(
+            values
+                .iter()
+                .filter(|name| {
+                    !placeholders.arguments.contains_key(*name)
+                        && !placeholders.tags.contains(*name)
+                })
+                .map(|name| Diagnostic::unused_value(usage, name)),
+        );
+    }
+
+    diagnostics
+}
+
+/// Keys of the reference locale that are never used, and not ignored.
+pub fn unused_keys(
+    locales: &LocaleSet,
+    usages: &UsageIndex,
+    ignore: &IgnoreList,
+) -> Vec<Diagnostic> {
+    let reference = &locales.reference().file;
+    reference
+        .entries
+        .iter()
+        .filter(|(key, _)| !usages.is_used(key) && !ignore.is_ignored(key))
+        .map(|(key, value)| Diagnostic::unused_key(key, value, reference.path.clone()))
+        .collect()
+}
+
+/// Entries of the ignore list that do not match any key of the reference locale.
+pub fn stale_ignores(locales: &LocaleSet, ignore: &IgnoreList) -> Vec<Diagnostic> {
+    let keys = locales
+        .reference()
+        .file
+        .entries
+        .keys()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    ignore
+        .stale_entries(&keys)
+        .into_iter()
+        .map(Diagnostic::stale_ignore)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        report::{Level, Rule, Severities, Severity},
+        ts_file::KeyUsage,
+    };
+
+    /// Rule and key of each diagnostic.
+    fn problems(diagnostics: &[Diagnostic]) -> Vec<(Rule, &str)> {
+        diagnostics
+            .iter()
+            .map(|d| (d.rule, d.key.as_deref().unwrap_or_default()))
+            .collect()
+    }
+
+    #[test]
+    fn test_reports_all_categories() {
+        let locales = LocaleSet::test();
+        let usage = |key: &str| KeyUsage::test(key, "Button.tsx");
+        let usages = UsageIndex::new([
+            usage("common.save"),
+            usage("common.cancel"),
+            usage("common.empty"),
+            usage("common.missing"),
+        ]);
+
+        let diagnostics = run(
+            &locales,
+            &usages,
+            &IgnoreList::default(),
+            &[],
+            &TranslationLock::default(),
+        );
+        let rules = diagnostics.iter().map(|d| d.rule).collect::<Vec<_>>();
+        for rule in [
+            Rule::DuplicateKey,
+            Rule::MissingKey,
+            Rule::EmptyValue,
+            Rule::InvalidKey,
+            Rule::UnusedKey,
+        ] {
+            assert!(rules.contains(&rule), "no {} diagnostic", rule.id());
+        }
+
+        let mut severities = Severities::default();
+        severities.set(Rule::UnusedKey, Level::Warning);
+        severities.set(Rule::EmptyValue, Level::Off);
+        let diagnostics = severities.apply(diagnostics);
+
+        assert!(!diagnostics.iter().any(|d| d.rule == Rule::EmptyValue));
+        assert!(diagnostics
+            .iter()
+            .filter(|d| d.rule == Rule::UnusedKey)
+            .all(|d| d.severity == Severity::Warning));
+        assert!(crate::report::has_errors(&diagnostics));
+    }
+
+    #[test]
+    fn test_invalid_messages() {
+        let mut locales = LocaleSet::test();
+        let sv = &mut locales.get_mut("sv").unwrap().file.entries;
+        sv.insert("common.save".to_string(), "Spara {".to_string());
+
+        let diagnostics = translation_files(&locales);
+
+        let invalid = problems(&diagnostics)
+            .into_iter()
+            .filter(|(rule, _)| *rule == Rule::InvalidMessage)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![(Rule::InvalidMessage, "common.save")], invalid);
+    }
+
+    #[test]
+    fn test_placeholder_mismatches() {
+        let mut locales = LocaleSet::test();
+        let en = &mut locales.get_mut("en").unwrap().file.entries;
+        en.insert("common.unused".to_string(), "Unused {name}".to_string());
+        let sv = &mut locales.get_mut("sv").unwrap().file.entries;
+        sv.insert("common.unused".to_string(), "Oanvänd {namn}".to_string());
+
+        let diagnostics = translation_files(&locales);
+
+        let mismatches = problems(&diagnostics)
+            .into_iter()
+            .filter(|(rule, _)| *rule == Rule::PlaceholderMismatch)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (Rule::PlaceholderMismatch, "common.unused"),
+                (Rule::PlaceholderMismatch, "common.unused"),
+            ],
+            mismatches
+        );
+    }
+
+    #[test]
+    fn test_untranslated_values() {
+        let mut locales = LocaleSet::test();
+        for name in ["en", "sv"] {
+            let entries = &mut locales.get_mut(name).unwrap().file.entries;
+            entries.insert("common.ok".to_string(), "OK".to_string());
+            entries.insert("common.email".to_string(), "Email".to_string());
+        }
+
+        let diagnostics = untranslated_values(&locales, &["OK".to_string()]);
+
+        assert_eq!(
+            vec![(Rule::Untranslated, "common.email")],
+            problems(&diagnostics)
+        );
+    }
+
+    #[test]
+    fn test_stale_translations() {
+        let mut locales = LocaleSet::test();
+        let mut lock = TranslationLock::default();
+        lock.update(&locales);
+        assert!(stale_translations(&locales, &lock).is_empty());
+
+        let en = &mut locales.get_mut("en").unwrap().file.entries;
+        en.insert("common.save".to_string(), "Save changes".to_string());
+
+        let diagnostics = stale_translations(&locales, &lock);
+
+        assert_eq!(
+            vec![(Rule::StaleTranslation, "common.save")],
+            problems(&diagnostics)
+        );
+    }
+
+    #[test]
+    fn test_message_values() {
+        let mut locales = LocaleSet::test();
+        let en = &mut locales.get_mut("en").unwrap().file.entries;
+        en.insert("common.cancel".to_string(), "Cancel {name}".to_string());
+        let usages = UsageIndex::new([
+            KeyUsage {
+                values: Some(vec!["count".to_string()]),
+                ..KeyUsage::test("common.cancel", "Button.tsx")
+            },
+            // Values that can not be known are not checked
+            KeyUsage::test("common.cancel", "Button.tsx"),
+        ]);
+
+        let diagnostics = message_values(&locales, &usages);
+
+        assert_eq!(
+            vec![
+                (Rule::MissingValue, "common.cancel"),
+                (Rule::UnusedValue, "common.cancel"),
+            ],
+            problems(&diagnostics)
+        );
+    }
+}
@@ -0,0 +1,403 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    ignore::{IgnoreError, IgnoreList},
+    locale::{LocaleError, LocaleSet, LocaleSpec},
+    lock::{LockError, TranslationLock},
+    report::Severities,
+    ts_file::UsagePatterns,
+};
+
+pub static CONFIG_FILE_NAME: &str = "ramilang.toml";
+static PACKAGE_JSON_SECTION: &str = "ramilang";
+
+/// Project configuration, read from `ramilang.toml` or the `ramilang` section of `package.json`.
+///
+/// Relative paths are relative to the directory of the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Translation file of each locale
+    pub locales: BTreeMap<String, PathBuf>,
+    /// Directory with one `<locale>.json` translation file per locale
+    pub locales_dir: Option<PathBuf>,
+    /// Locale that all other locales are checked against
+    pub reference: Option<String>,
+    /// Directories to search for key usages
+    pub roots: Vec<PathBuf>,
+    /// Extensions of the files to search for key usages
+    pub extensions: Vec<String>,
+    /// If not empty, only files matching one of these globs are searched
+    pub include: Vec<String>,
+    /// Files and directories matching these globs are never searched
+    pub exclude: Vec<String>,
+    /// File with keys to exclude from the unused check, one key, glob or `/regex/` per line
+    pub ignore_file: Option<PathBuf>,
+    /// Keys, globs or `/regex/` to exclude from the unused check, after those of the file
+    pub ignore: Vec<String>,
+    pub patterns: UsagePatterns,
+    /// Values that may be the same as in the reference locale, like brand names or "OK"
+    pub allow_untranslated: Vec<String>,
+    /// Where the reference value of each translation is recorded (default: ramilang.lock next to
+    /// the translation file of the reference locale)
+    pub lock_file: Option<PathBuf>,
+    /// Problems that are known, only problems that are not in it fail a check
+    pub baseline_file: PathBuf,
+    /// Level of each rule, rules that are not listed have their default level
+    pub severity: Severities,
+    /// Whether to cache the key usages of unchanged source files between runs
+    pub cache: bool,
+    /// Where the cache is stored (default: node_modules/.cache/ramilang/usages.json, if there is
+    /// a node_modules directory)
+    pub cache_file: Option<PathBuf>,
+    /// Directory of the config file
+    #[serde(skip)]
+    dir: PathBuf,
+}
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("could not read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid config in {path}: {source}")]
+    Toml {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("invalid config in {path}: {source}")]
+    Json {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("{0} has no \"ramilang\" section")]
+    MissingSection(PathBuf),
+    #[error("invalid glob \"{glob}\": {source}")]
+    Glob {
+        glob: String,
+        source: globset::Error,
+    },
+}
+
+/// Decides which files to search for key usages.
+#[derive(Debug, Clone)]
+pub struct SourceFilter {
+    extensions: Vec<String>,
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            locales: BTreeMap::new(),
+            locales_dir: None,
+            reference: None,
+            roots: vec![PathBuf::from(".")],
+            extensions: vec!["ts".to_string(), "tsx".to_string()],
+            include: Vec::new(),
+            exclude: vec!["**/node_modules".to_string()],
+            ignore_file: None,
+            ignore: Vec::new(),
+            patterns: UsagePatterns::default(),
+            allow_untranslated: Vec::new(),
+            lock_file: None,
+            baseline_file: PathBuf::from("ramilang-baseline.json"),
+            severity: Severities::default(),
+            cache: true,
+            cache_file: None,
+            dir: PathBuf::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Look for a `ramilang.toml`, or a `package.json` with a `ramilang` section, in `dir` and
+    /// then in each of its parents.
+    pub fn discover(dir: &Path) -> Result<Option<(PathBuf, Self)>, ConfigError> {
+        let dir = std::path::absolute(dir).map_err(|source| ConfigError::Io {
+            path: dir.to_path_buf(),
+            source,
+        })?;
+
+        // Keep paths relative to the working directory when possible
+        let current_dir = std::env::current_dir().unwrap_or_default();
+        let relative = |path: PathBuf| match path.strip_prefix(&current_dir) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => path,
+        };
+
+        for ancestor in dir.ancestors() {
+            let toml_path = relative(ancestor.join(CONFIG_FILE_NAME));
+            if toml_path.is_file() {
+                let config = Self::load(&toml_path)?;
+                return Ok(Some((toml_path, config)));
+            }
+
+            let package_json_path = relative(ancestor.join("package.json"));
+            if package_json_path.is_file() {
+                match Self::load(&package_json_path) {
+                    Ok(config) => return Ok(Some((package_json_path, config))),
+                    Err(ConfigError::MissingSection(_)) => {}
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Load a `ramilang.toml`, or the `ramilang` section of a `package.json`.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let content = std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        let mut config = if path.extension().is_some_and(|ext| ext == "json") {
+            let json_error = |source| ConfigError::Json {
+                path: path.to_path_buf(),
+                source,
+            };
+            let mut package: serde_json::Map<String, serde_json::Value> =
+                serde_json::from_str(&content).map_err(json_error)?;
+            let section = package
+                .remove(PACKAGE_JSON_SECTION)
+                .ok_or_else(|| ConfigError::MissingSection(path.to_path_buf()))?;
+            serde_json::from_value(section).map_err(json_error)?
+        } else {
+            toml::from_str::<Self>(&content).map_err(|source| ConfigError::Toml {
+                path: path.to_path_buf(),
+                source,
+            })?
+        };
+
+        config.resolve_paths(path.parent().unwrap_or(Path::new("")));
+        Ok(config)
+    }
+
+    fn resolve_paths(&mut self, base: &Path) {
+        for path in self.locales.values_mut() {
+            *path = base.join(&path);
+        }
+        for root in self.roots.iter_mut() {
+            *root = base.join(&root);
+        }
+        if let Some(locales_dir) = self.locales_dir.as_mut() {
+            *locales_dir = base.join(&locales_dir);
+        }
+        if let Some(ignore_file) = self.ignore_file.as_mut() {
+            *ignore_file = base.join(&ignore_file);
+        }
+        if let Some(lock_file) = self.lock_file.as_mut() {
+            *lock_file = base.join(&lock_file);
+        }
+        self.baseline_file = base.join(&self.baseline_file);
+        if let Some(cache_file) = self.cache_file.as_mut() {
+            *cache_file = base.join(&cache_file);
+        }
+        self.dir = base.to_path_buf();
+    }
+
+    /// Where the cache is stored, `None` if caching is disabled. Without a `cache_file`, the
+    /// cache is only kept in an existing `node_modules` directory, so that projects without one
+    /// do not get one.
+    pub fn cache_file(&self) -> Option<PathBuf> {
+        if !self.cache {
+            return None;
+        }
+        if let Some(cache_file) = &self.cache_file {
+            return Some(cache_file.clone());
+        }
+        let node_modules = self.dir.join("node_modules");
+        node_modules
+            .is_dir()
+            .then(|| node_modules.join(".cache/ramilang/usages.json"))
+    }
+
+    /// The locales from `locales` and `locales_dir`.
+    pub fn locale_specs(&self) -> Result<Vec<LocaleSpec>, LocaleError> {
+        let mut specs = self
+            .locales
+            .iter()
+            .map(|(name, path)| LocaleSpec {
+                name: name.clone(),
+                path: path.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(locales_dir) = &self.locales_dir {
+            for spec in LocaleSpec::discover(locales_dir)? {
+                // Explicitly given locales take precedence over discovered ones
+                if !specs.iter().any(|other| other.name == spec.name) {
+                    specs.push(spec);
+                }
+            }
+        }
+
+        Ok(specs)
+    }
+
+    /// The entries of `ignore_file` followed by those of `ignore`.
+    pub fn ignore_list(&self) -> Result<IgnoreList, IgnoreError> {
+        let mut list = match &self.ignore_file {
+            Some(ignore_file) => IgnoreList::load(ignore_file)?,
+            None => IgnoreList::default(),
+        };
+        for pattern in &self.ignore {
+            list.add(pattern, None, None)?;
+        }
+        Ok(list)
+    }
+
+    /// The lock file of `lock_file`, or the one next to the translation file of the reference
+    /// locale.
+    pub fn translation_lock(&self, locales: &LocaleSet) -> Result<TranslationLock, LockError> {
+        match &self.lock_file {
+            Some(lock_file) => TranslationLock::load(lock_file),
+            None => TranslationLock::load(&TranslationLock::default_path(locales)),
+        }
+    }
+
+    pub fn source_filter(&self) -> Result<SourceFilter, ConfigError> {
+        let include = if self.include.is_empty() {
+            None
+        } else {
+            Some(build_glob_set(&self.include)?)
+        };
+
+        Ok(SourceFilter {
+            extensions: self.extensions.clone(),
+            include,
+            exclude: build_glob_set(&self.exclude)?,
+        })
+    }
+}
+
+impl SourceFilter {
+    /// Whether a file or directory, relative to the root being searched, is excluded. Excluded
+    /// directories are not searched at all.
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        self.exclude.is_match(path)
+    }
+
+    /// Whether a file, relative to the root being searched, should be searched for key usages.
+    pub fn is_source_file(&self, path: &Path) -> bool {
+        let has_extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| self.extensions.iter().any(|other| other == ext));
+
+        has_extension
+            && !self.is_excluded(path)
+            && self
+                .include
+                .as_ref()
+                .is_none_or(|include| include.is_match(path))
+    }
+
+    /// Whether a file, relative to the root being searched, is found when searching the root.
+    /// Unlike `is_source_file`, this also checks that none of its parent directories are excluded.
+    pub fn contains(&self, path: &Path) -> bool {
+        self.is_source_file(path)
+            && !path
+                .ancestors()
+                .skip(1)
+                .any(|parent| !parent.as_os_str().is_empty() && self.is_excluded(parent))
+    }
+}
+
+fn build_glob_set(globs: &[String]) -> Result<GlobSet, ConfigError> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(Glob::new(glob).map_err(|source| ConfigError::Glob {
+            glob: glob.clone(),
+            source,
+        })?);
+    }
+    builder.build().map_err(|source| ConfigError::Glob {
+        glob: globs.join(", "),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{Level, Rule};
+
+    #[test]
+    fn test_parse_toml_config() {
+        let config: Config = toml::from_str(
+            r#"
+            reference = "en"
+            exclude = ["**/node_modules", "dist/**"]
+
+            [locales]
+            en = "translations/en.json"
+            no = "translations/no.json"
+
+            [patterns]
+            props = ["labelId"]
+
+            [severity]
+            unused-key = "warning"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(Some("en".to_string()), config.reference);
+        assert_eq!(2, config.locales.len());
+        assert_eq!(vec!["labelId".to_string()], config.patterns.props);
+        // Unspecified values keep their defaults
+        assert_eq!(vec!["formatMessage".to_string()], config.patterns.calls);
+        assert_eq!(vec![PathBuf::from(".")], config.roots);
+        assert_eq!(Level::Warning, config.severity.level(Rule::UnusedKey));
+        assert_eq!(Level::Error, config.severity.level(Rule::MissingKey));
+    }
+
+    #[test]
+    fn test_cache_file() {
+        let dir = std::env::temp_dir().join("ramilang_test_cache_file");
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut config = Config {
+            dir: dir.clone(),
+            ..Config::default()
+        };
+        assert_eq!(None, config.cache_file());
+
+        std::fs::create_dir_all(dir.join("node_modules")).unwrap();
+        assert_eq!(
+            Some(dir.join("node_modules/.cache/ramilang/usages.json")),
+            config.cache_file()
+        );
+        config.cache = false;
+        assert_eq!(None, config.cache_file());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_source_filter() {
+        let config = Config {
+            include: vec!["src/**".to_string()],
+            exclude: vec!["**/node_modules".to_string(), "**/*.test.tsx".to_string()],
+            ..Config::default()
+        };
+        let filter = config.source_filter().unwrap();
+
+        assert!(filter.is_source_file(Path::new("src/components/Button.tsx")));
+        assert!(!filter.is_source_file(Path::new("src/components/Button.test.tsx")));
+        assert!(!filter.is_source_file(Path::new("src/components/Button.css")));
+        assert!(!filter.is_source_file(Path::new("scripts/build.ts")));
+        assert!(filter.is_excluded(Path::new("packages/ui/node_modules")));
+    }
+}
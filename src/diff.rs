@@ -0,0 +1,423 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    path::Path,
+};
+
+use clap::ValueEnum;
+use console::style;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    git::{GitError, Repository},
+    locale::{LocaleError, LocaleSet, LocaleSpec},
+    translation_file::{TranslationFile, TranslationFileError},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DiffFormat {
+    /// Colored text for humans
+    Text,
+    /// A Markdown table, for example for a pull request comment
+    Markdown,
+    /// JSON with every changed key
+    Json,
+}
+
+/// The keys that changed between two versions of the translation files.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TranslationDiff {
+    /// Locales of both versions, starting with the reference
+    pub locales: Vec<String>,
+    /// Changed keys, sorted by key
+    pub changes: Vec<KeyChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeyChange {
+    pub kind: ChangeKind,
+    /// The key in the new version, or the removed key
+    pub key: String,
+    /// The key in the old version, for a renamed key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renamed_from: Option<String>,
+    /// The old and new value of each locale
+    pub values: BTreeMap<String, ValueChange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    /// A key with the same reference value as a removed key
+    Renamed,
+    /// A key with another value in some locale
+    Changed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValueChange {
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+#[derive(Error, Debug)]
+pub enum DiffError {
+    #[error(transparent)]
+    Git(#[from] GitError),
+    #[error(transparent)]
+    Locale(#[from] LocaleError),
+}
+
+impl From<TranslationFileError> for DiffError {
+    fn from(err: TranslationFileError) -> Self {
+        DiffError::Locale(err.into())
+    }
+}
+
+/// A version of the translation files: a translation file, or a directory with one
+/// `<locale>.json` file per locale. A single file is named after `name`, so that two versions
+/// of a file can be compared whatever they are called.
+pub fn load_version(path: &Path, name: &str) -> Result<LocaleSet, LocaleError> {
+    let specs = if path.is_dir() {
+        LocaleSpec::discover(path)?
+    } else {
+        vec![LocaleSpec {
+            name: name.to_string(),
+            path: path.to_path_buf(),
+        }]
+    };
+    LocaleSet::load(specs, None)
+}
+
+/// The translation files of `locales` where the current branch left `since`. A file that did not
+/// exist there is empty.
+pub fn load_since(
+    repository: &Repository,
+    since: &str,
+    locales: &LocaleSet,
+) -> Result<LocaleSet, DiffError> {
+    let merge_base = repository.merge_base(since)?;
+    let mut old = locales.clone();
+    for locale in old.iter_mut() {
+        let path = locale.file.path.clone();
+        let content = repository.show(&merge_base, &path)?;
+        locale.file = TranslationFile::parse(path, content.as_deref().unwrap_or("{}"))?;
+    }
+    Ok(old)
+}
+
+impl TranslationDiff {
+    pub fn new(old: &LocaleSet, new: &LocaleSet) -> Self {
+        let mut locales = new.names();
+        for name in old.names() {
+            if !locales.contains(&name) {
+                locales.push(name);
+            }
+        }
+        let value = |set: &LocaleSet, locale: &str, key: &str| {
+            set.get(locale)
+                .and_then(|locale| locale.file.entries.get(key).cloned())
+        };
+        let change = |kind, key: &str, renamed_from: Option<&str>| KeyChange {
+            kind,
+            key: key.to_string(),
+            renamed_from: renamed_from.map(str::to_string),
+            values: locales
+                .iter()
+                .map(|locale| {
+                    let change = ValueChange {
+                        old: value(old, locale, renamed_from.unwrap_or(key)),
+                        new: value(new, locale, key),
+                    };
+                    (locale.clone(), change)
+                })
+                .collect(),
+        };
+
+        let keys = old
+            .iter()
+            .chain(new.iter())
+            .flat_map(|locale| locale.file.entries.keys())
+            .collect::<BTreeSet<_>>();
+        // Keys are added and removed with the reference locale, other locales can lag behind
+        let reference = &locales[0];
+        let contains = |set: &LocaleSet, key: &str| {
+            let in_reference = |set: &LocaleSet| value(set, reference, key).is_some();
+            if in_reference(old) || in_reference(new) {
+                in_reference(set)
+            } else {
+                locales.iter().any(|l| value(set, l, key).is_some())
+            }
+        };
+        let mut added = Vec::new();
+        let mut removed = Vec::new();
+        let mut changes = Vec::new();
+        for key in keys {
+            match (contains(old, key), contains(new, key)) {
+                (false, _) => added.push(key),
+                (_, false) => removed.push(key),
+                _ if locales
+                    .iter()
+                    .any(|l| value(old, l, key) != value(new, l, key)) =>
+                {
+                    changes.push(change(ChangeKind::Changed, key, None))
+                }
+                _ => {}
+            }
+        }
+
+        // An added key with the same reference value as a removed key is that key renamed
+        for key in added {
+            let reference_value = value(new, reference, key).filter(|value| !value.is_empty());
+            let renamed = removed.iter().position(|removed| {
+                reference_value.is_some() && value(old, reference, removed) == reference_value
+            });
+            changes.push(match renamed {
+                Some(i) => change(ChangeKind::Renamed, key, Some(removed.remove(i))),
+                None => change(ChangeKind::Added, key, None),
+            });
+        }
+        changes.extend(
+            removed
+                .into_iter()
+                .map(|key| change(ChangeKind::Removed, key, None)),
+        );
+        changes.sort_by(|a, b| a.key.cmp(&b.key));
+
+        Self { locales, changes }
+    }
+
+    pub fn render(&self, format: DiffFormat) -> String {
+        match format {
+            DiffFormat::Text => self.to_text(),
+            DiffFormat::Markdown => self.to_markdown(),
+            DiffFormat::Json => format!(
+                "{}\n",
+                serde_json::to_string_pretty(self).unwrap_or_default()
+            ),
+        }
+    }
+
+    /// Every changed key with the value of each locale below it.
+    pub fn to_text(&self) -> String {
+        let width = self.locales.iter().map(String::len).max().unwrap_or(0);
+        let quote = |value: &str| serde_json::to_string(value).unwrap_or_default();
+
+        let mut text = String::new();
+        for change in &self.changes {
+            let header = match change.kind {
+                ChangeKind::Added => style(format!("+ {}", change.key)).green(),
+                ChangeKind::Removed => style(format!("- {}", change.key)).red(),
+                ChangeKind::Renamed => style(format!(
+                    "> {} (renamed from {})",
+                    change.key,
+                    change.renamed_from.as_deref().unwrap_or_default()
+                ))
+                .blue(),
+                ChangeKind::Changed => style(format!("~ {}", change.key)).yellow(),
+            };
+            let _ = writeln!(text, "{}", header.bold());
+
+            for (locale, value) in self.values(change) {
+                let value = match (change.kind, &value.old, &value.new) {
+                    (_, None, None) => style("(none)".to_string()).dim().to_string(),
+                    (_, old, new) if old == new => quote(new.as_deref().unwrap_or_default()),
+                    (ChangeKind::Added, _, Some(new)) => style(quote(new)).green().to_string(),
+                    (ChangeKind::Removed, Some(old), _) => style(quote(old)).red().to_string(),
+                    (_, old, new) => {
+                        let show = |value: &Option<String>| {
+                            value.as_deref().map_or("(none)".to_string(), quote)
+                        };
+                        format!("{} -> {}", style(show(old)).red(), style(show(new)).green())
+                    }
+                };
+                let _ = writeln!(text, "    {:<width$}  {}", locale, value);
+            }
+        }
+
+        if !self.changes.is_empty() {
+            text.push('\n');
+        }
+        let _ = writeln!(text, "{}", style(self.summary()).bold());
+        text
+    }
+
+    /// A table with a row per changed key and a column per locale.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("**Translations: {}**\n", self.summary());
+        if self.changes.is_empty() {
+            return markdown;
+        }
+
+        let _ = writeln!(markdown, "\n| | Key | {} |", self.locales.join(" | "));
+        let _ = writeln!(markdown, "|---|---|{}", "---|".repeat(self.locales.len()));
+        for change in &self.changes {
+            let kind = match change.kind {
+                ChangeKind::Added => "Added",
+                ChangeKind::Removed => "Removed",
+                ChangeKind::Renamed => "Renamed",
+                ChangeKind::Changed => "Changed",
+            };
+            let key = match &change.renamed_from {
+                Some(from) => format!("{} → {}", markdown_code(from), markdown_code(&change.key)),
+                None => markdown_code(&change.key),
+            };
+            let values = self
+                .values(change)
+                .map(|(_, value)| match (change.kind, &value.old, &value.new) {
+                    (_, None, None) => String::new(),
+                    (_, old, new) if old == new => {
+                        markdown_cell(new.as_deref().unwrap_or_default())
+                    }
+                    (ChangeKind::Added, _, Some(new)) => markdown_cell(new),
+                    (ChangeKind::Removed, Some(old), _) => format!("~~{}~~", markdown_cell(old)),
+                    (_, old, new) => {
+                        let show = |value: &Option<String>| {
+                            value.as_deref().map_or("*none*".to_string(), markdown_cell)
+                        };
+                        format!("~~{}~~ → {}", show(old), show(new))
+                    }
+                })
+                .collect::<Vec<_>>();
+            let _ = writeln!(markdown, "| {} | {} | {} |", kind, key, values.join(" | "));
+        }
+        markdown
+    }
+
+    /// E.g. "2 added, 1 removed, 0 renamed and 3 changed keys".
+    pub fn summary(&self) -> String {
+        let count = |kind| {
+            self.changes
+                .iter()
+                .filter(|change| change.kind == kind)
+                .count()
+        };
+        format!(
+            "{} added, {} removed, {} renamed and {} changed keys",
+            count(ChangeKind::Added),
+            count(ChangeKind::Removed),
+            count(ChangeKind::Renamed),
+            count(ChangeKind::Changed)
+        )
+    }
+
+    /// The values of `change` in the order of the locales.
+    fn values<'a>(
+        &'a self,
+        change: &'a KeyChange,
+    ) -> impl Iterator<Item = (&'a String, &'a ValueChange)> {
+        self.locales
+            .iter()
+            .filter_map(|locale| Some((locale, change.values.get(locale)?)))
+    }
+}
+
+/// A value that can be put in a Markdown table cell.
+fn markdown_cell(value: &str) -> String {
+    if value.is_empty() {
+        return "*empty*".to_string();
+    }
+    let mut cell = String::with_capacity(value.len());
+    for c in value.replace("\r\n", "\n").chars() {
+        match c {
+            '&' => cell.push_str("&amp;"),
+            '<' => cell.push_str("&lt;"),
+            '>' => cell.push_str("&gt;"),
+            '\n' => cell.push_str("<br>"),
+            '\\' | '|' | '*' | '_' | '`' | '~' => {
+                cell.push('\\');
+                cell.push(c);
+            }
+            _ => cell.push(c),
+        }
+    }
+    cell
+}
+
+/// A key as inline code in a Markdown table cell, fenced by more backticks than it contains.
+fn markdown_code(key: &str) -> String {
+    let longest_run = key
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(longest_run + 1);
+    // Code spans strip one space at each end, which keeps backticks at the ends apart from the fence
+    let padding = if key.starts_with('`') || key.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+    // Tables split cells on pipes even inside code spans
+    let key = key.replace('|', "\\|");
+    format!("{fence}{padding}{key}{padding}{fence}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let old = LocaleSet::test();
+        let mut new = LocaleSet::test();
+        for (name, value) in [("en", "Unused"), ("sv", "Oanvänd")] {
+            let entries = &mut new.get_mut(name).unwrap().file.entries;
+            entries.remove("common.unused");
+            entries.insert("common.renamed".to_string(), value.to_string());
+        }
+        let sv = &mut new.get_mut("sv").unwrap().file.entries;
+        sv.insert("common.save".to_string(), "Spara ändringar".to_string());
+        sv.insert("common.new".to_string(), "Ny".to_string());
+        sv.remove("common.extra");
+
+        let diff = TranslationDiff::new(&old, &new);
+
+        let changes = diff
+            .changes
+            .iter()
+            .map(|change| (change.kind, change.key.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (ChangeKind::Removed, "common.extra"),
+                (ChangeKind::Added, "common.new"),
+                (ChangeKind::Renamed, "common.renamed"),
+                (ChangeKind::Changed, "common.save"),
+            ],
+            changes
+        );
+        assert_eq!(
+            Some("common.unused"),
+            diff.changes[2].renamed_from.as_deref()
+        );
+        assert_eq!(
+            ValueChange {
+                old: Some("Spara".to_string()),
+                new: Some("Spara ändringar".to_string()),
+            },
+            diff.changes[3].values["sv"]
+        );
+        assert!(diff
+            .to_markdown()
+            .contains("| Changed | `common.save` | Save! | ~~Spara~~ → Spara ändringar |"));
+    }
+
+    #[test]
+    fn test_markdown_escaping() {
+        let old = LocaleSet::test();
+        let mut new = LocaleSet::test();
+        new.get_mut("en").unwrap().file.entries.insert(
+            "`common.greeting`|".to_string(),
+            "Hello <b>{name}</b> & *you*_`".to_string(),
+        );
+
+        let markdown = TranslationDiff::new(&old, &new).to_markdown();
+        assert!(markdown.contains(
+            "| Added | `` `common.greeting`\\| `` | Hello &lt;b&gt;{name}&lt;/b&gt; &amp; \\*you\\*\\_\\` |"
+        ));
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::BTreeSet;
+
+use clap::ValueEnum;
+use serde_json::{Map, Value};
+
+use crate::locale::LocaleSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// One row per key and one column per locale
+    Csv,
+    /// An object per key, with the value of each locale
+    Json,
+}
+
+/// Every key with its value in each locale, sorted by key. A locale that lacks a key has an
+/// empty cell in CSV and `null` in JSON.
+///
+/// With `untranslated`, only the keys that some locale lacks or has an empty value for are
+/// exported, which is what a translator needs.
+pub fn export(locales: &LocaleSet, format: ExportFormat, untranslated: bool) -> String {
+    let keys = locales
+        .iter()
+        .flat_map(|locale| locale.file.entries.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter(|key| {
+            !untranslated
+                || locales
+                    .iter()
+                    .any(|locale| locale.file.entries.get(*key).is_none_or(String::is_empty))
+        });
+
+    match format {
+        ExportFormat::Csv => {
+            let header = std::iter::once("key".to_string())
+                .chain(locales.names())
+                .map(|cell| csv_cell(&cell))
+                .collect::<Vec<_>>()
+                .join(",");
+            let mut csv = format!("{}\n", header);
+            for key in keys {
+                let row = std::iter::once(key.as_str())
+                    .chain(
+                        locales
+                            .iter()
+                            .map(|locale| locale.file.entries.get(key).map_or("", String::as_str)),
+                    )
+                    .map(csv_cell)
+                    .collect::<Vec<_>>()
+                    .join(",");
+                csv.push_str(&row);
+                csv.push('\n');
+            }
+            csv
+        }
+        ExportFormat::Json => {
+            let object = keys
+                .map(|key| {
+                    let values = locales
+                        .iter()
+                        .map(|locale| {
+                            let value = locale.file.entries.get(key);
+                            (locale.name.clone(), value.cloned().into())
+                        })
+                        .collect::<Map<_, _>>();
+                    (key.clone(), Value::Object(values))
+                })
+                .collect::<Map<_, _>>();
+            format!("{:#}\n", Value::Object(object))
+        }
+    }
+}
+
+/// Quote a CSV cell if it needs it, as in RFC 4180.
+fn csv_cell(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_csv() {
+        let csv = export(&LocaleSet::test(), ExportFormat::Csv, false);
+        assert_eq!(
+            "key,en,sv\n\
+             common.cancel,Cancel,\n\
+             common.empty,,Tom\n\
+             common.extra,,Extra\n\
+             common.save,Save!,Spara\n\
+             common.unused,Unused,Oanvänd\n",
+            csv
+        );
+        assert_eq!("\"say \"\"hi\"\", bye\"", csv_cell("say \"hi\", bye"));
+    }
+
+    #[test]
+    fn test_export_untranslated_json() {
+        let json = export(&LocaleSet::test(), ExportFormat::Json, true);
+        let value: Value = serde_json::from_str(&json).unwrap();
+        let keys = value.as_object().unwrap().keys().collect::<Vec<_>>();
+        assert_eq!(vec!["common.cancel", "common.empty", "common.extra"], keys);
+        assert_eq!(Value::Null, value["common.cancel"]["sv"]);
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::BTreeSet;
+
+use clap::ValueEnum;
+use console::style;
+use similar::{ChangeTag, TextDiff};
+
+use crate::{ignore::IgnoreList, locale::LocaleSet, usage_index::UsageIndex};
+
+/// Prefix of the values added with [`MissingValue::Todo`].
+pub static TODO_PREFIX: &str = "TODO: ";
+
+/// Value given to a key that is added to a locale that lacks it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MissingValue {
+    /// An empty string
+    Empty,
+    /// The value of the reference locale
+    Copy,
+    /// The value of the reference locale prefixed with `TODO: `
+    Todo,
+}
+
+/// What a fix changed.
+#[derive(Debug, Default, PartialEq)]
+pub struct FixSummary {
+    /// Keys removed from all locales because they are not used
+    pub removed: Vec<String>,
+    /// Locale and key of every key that was added
+    pub added: Vec<(String, String)>,
+}
+
+/// Remove unused keys from all locales if `remove_unused` is set, then add the keys that some
+/// locales lack.
+///
+/// Keys that only exist in some of the other locales count too, so that no key is added just to
+/// be unused. Only the entries are changed, the files are not written.
+pub fn fix(
+    locales: &mut LocaleSet,
+    usages: &UsageIndex,
+    ignore: &IgnoreList,
+    missing: MissingValue,
+    remove_unused: bool,
+) -> FixSummary {
+    let mut summary = FixSummary::default();
+    let all_keys = locales
+        .iter()
+        .flat_map(|locale| locale.file.entries.keys().cloned())
+        .collect::<BTreeSet<_>>();
+
+    for key in all_keys {
+        if !usages.is_used(&key) && !ignore.is_ignored(&key) {
+            if remove_unused {
+                for locale in locales.iter_mut() {
+                    locale.file.entries.remove(&key);
+                }
+                summary.removed.push(key);
+            }
+            continue;
+        }
+
+        // The reference value if there is one, otherwise the value of the first locale with it
+        let Some(source) = locales
+            .iter()
+            .find_map(|locale| locale.file.entries.get(&key).cloned())
+        else {
+            continue;
+        };
+        let value = match missing {
+            MissingValue::Empty => String::new(),
+            MissingValue::Copy => source,
+            MissingValue::Todo => format!("{}{}", TODO_PREFIX, source),
+        };
+
+        for locale in locales.iter_mut() {
+            if !locale.file.entries.contains_key(&key) {
+                locale.file.entries.insert(key.clone(), value.clone());
+                summary.added.push((locale.name.clone(), key.clone()));
+            }
+        }
+    }
+
+    summary
+}
+
+/// A colored unified diff of what writing the translation files would change, empty if nothing
+/// would change.
+pub fn diff(locales: &LocaleSet) -> anyhow::Result<String> {
+    let mut output = String::new();
+    for locale in locales.iter() {
+        let path = &locale.file.path;
+        let old = std::fs::read_to_string(path)?;
+        let new = locale.file.serialize()?;
+        if old == new {
+            continue;
+        }
+
+        let path = path.display().to_string();
+        let diff = TextDiff::from_lines(&old, &new);
+        output.push_str(&format!(
+            "{}\n{}\n",
+            style(format!("--- {}", path)).bold(),
+            style(format!("+++ {}", path)).bold()
+        ));
+        for hunk in diff.unified_diff().iter_hunks() {
+            output.push_str(&format!("{}\n", style(hunk.header()).cyan()));
+            for change in hunk.iter_changes() {
+                let line = format!("{}{}", change.tag(), change.value().trim_end());
+                let line = match change.tag() {
+                    ChangeTag::Delete => style(line).red(),
+                    ChangeTag::Insert => style(line).green(),
+                    ChangeTag::Equal => style(line),
+                };
+                output.push_str(&format!("{}\n", line));
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        scan::{find_key_usages, UsageCache},
+        ts_file::{KeyUsage, UsagePatterns},
+    };
+
+    #[test]
+    fn test_fix() {
+        let mut locales = LocaleSet::test();
+        let usages = UsageIndex::new([
+            KeyUsage::test("common.save", "Button.tsx"),
+            KeyUsage::test("common.cancel", "Button.tsx"),
+        ]);
+        let mut ignore = IgnoreList::default();
+        ignore.add("common.extra", None, None).unwrap();
+
+        let summary = fix(&mut locales, &usages, &ignore, MissingValue::Todo, true);
+
+        assert_eq!(vec!["common.empty", "common.unused"], summary.removed);
+        assert_eq!(
+            vec![
+                ("sv".to_string(), "common.cancel".to_string()),
+                ("en".to_string(), "common.extra".to_string()),
+            ],
+            summary.added
+        );
+        let sv = &locales.get("sv").unwrap().file.entries;
+        assert_eq!(Some(&"TODO: Cancel".to_string()), sv.get("common.cancel"));
+        assert!(locales.is_compatible().is_ok());
+    }
+
+    #[test]
+    fn test_unreadable_files_keep_their_keys() {
+        let path = std::env::temp_dir().join("ramilang_test_fix_unreadable.tsx");
+        std::fs::write(&path, b"formatMessage({ id: \"common.unused\" }); // \xff").unwrap();
+        let files = vec![path.clone()];
+        let patterns = UsagePatterns::default();
+        let mut cache = UsageCache::new(&patterns);
+        let (usages, errors) = find_key_usages(&files, &patterns, &mut cache);
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(1, errors.len());
+
+        let mut locales = LocaleSet::test();
+        let usages = UsageIndex::new(usages);
+        let summary = fix(
+            &mut locales,
+            &usages,
+            &IgnoreList::default(),
+            MissingValue::Todo,
+            errors.is_empty(),
+        );
+
+        assert!(summary.removed.is_empty());
+        let en = &locales.get("en").unwrap().file.entries;
+        assert!(en.contains_key("common.unused"));
+    }
+}
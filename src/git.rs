@@ -0,0 +1,122 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use thiserror::Error;
+
+/// The local git repository, read with the `git` command.
+#[derive(Debug, Clone)]
+pub struct Repository {
+    /// The top level directory, canonicalized
+    root: PathBuf,
+}
+
+#[derive(Error, Debug)]
+pub enum GitError {
+    #[error("could not run git: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("`git {command}` failed: {message}")]
+    Command { command: String, message: String },
+    #[error("{0} is not in the git repository")]
+    OutsideRepository(PathBuf),
+}
+
+impl Repository {
+    /// The repository that `dir` is in.
+    pub fn discover(dir: &Path) -> Result<Self, GitError> {
+        let root = git(dir, &["rev-parse", "--show-toplevel"])?;
+        Ok(Self {
+            root: std::fs::canonicalize(root.trim())?,
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The commit that `revision` and `HEAD` branched from.
+    pub fn merge_base(&self, revision: &str) -> Result<String, GitError> {
+        let commit = self.git(&["merge-base", revision, "HEAD"])?;
+        Ok(commit.trim().to_string())
+    }
+
+    /// Files that differ between `revision` and the working tree, including untracked files.
+    pub fn changed_files(&self, revision: &str) -> Result<Vec<PathBuf>, GitError> {
+        let mut files = self.git(&["diff", "--name-only", "-z", revision])?;
+        files.push_str(&self.git(&["ls-files", "--others", "--exclude-standard", "-z"])?);
+        Ok(self.paths(&files))
+    }
+
+    /// Files that differ between `HEAD` and the index.
+    pub fn staged_files(&self) -> Result<Vec<PathBuf>, GitError> {
+        let files = self.git(&["diff", "--name-only", "-z", "--cached"])?;
+        Ok(self.paths(&files))
+    }
+
+    /// The content of `path` at `revision`, or in the index if `revision` is empty. `None` if
+    /// the file does not exist there.
+    pub fn show(&self, revision: &str, path: &Path) -> Result<Option<String>, GitError> {
+        let object = format!("{}:{}", revision, self.relative(path)?);
+        let exists = Command::new("git")
+            .args(["cat-file", "-e", &object])
+            .current_dir(&self.root)
+            .output()?
+            .status
+            .success();
+        if !exists {
+            return Ok(None);
+        }
+        self.git(&["show", &object]).map(Some)
+    }
+
+    /// `path` relative to the root of the repository, with `/` as separator.
+    pub fn relative(&self, path: &Path) -> Result<String, GitError> {
+        let canonical = canonicalize(path);
+        let relative = canonical
+            .strip_prefix(&self.root)
+            .map_err(|_| GitError::OutsideRepository(path.to_path_buf()))?;
+        Ok(relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"))
+    }
+
+    fn git(&self, args: &[&str]) -> Result<String, GitError> {
+        git(&self.root, args)
+    }
+
+    /// Absolute paths of the NUL separated paths of `output`, which are relative to the root.
+    fn paths(&self, output: &str) -> Vec<PathBuf> {
+        output
+            .split('\0')
+            .filter(|line| !line.is_empty())
+            .map(|line| self.root.join(line))
+            .collect()
+    }
+}
+
+/// `path` canonicalized, or only made absolute if it does not exist (anymore).
+pub fn canonicalize(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| {
+        // The parent usually still exists, even if the file was deleted
+        match (path.parent(), path.file_name()) {
+            (Some(parent), Some(name)) if !parent.as_os_str().is_empty() => {
+                canonicalize(parent).join(name)
+            }
+            _ => std::env::current_dir().unwrap_or_default().join(path),
+        }
+    })
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String, GitError> {
+    let output = Command::new("git").args(args).current_dir(dir).output()?;
+    if !output.status.success() {
+        return Err(GitError::Command {
+            command: args.join(" "),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
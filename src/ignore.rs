@@ -0,0 +1,245 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use regex::Regex;
+use thiserror::Error;
+
+/// Keys to exclude from the unused check, read from an ignore file like `.keyignore`.
+///
+/// Each line is a key, a glob like `errors.api.*` or a regex like `/^enum\.status\.\d+$/`.
+/// Lines starting with `#` are comments, and lines starting with `!` un-ignore the keys they
+/// match. As in `.gitignore`, the last matching line decides.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreList {
+    entries: Vec<IgnoreEntry>,
+    /// Index of the last entry with each literal key
+    literals: HashMap<String, usize>,
+    /// Indices of the entries with a glob or regex
+    patterns: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct IgnoreEntry {
+    /// The entry as written, without the `!`
+    pub pattern: String,
+    pub negated: bool,
+    /// The ignore file and line the entry is from, `None` for entries from the config
+    pub file: Option<PathBuf>,
+    pub line: Option<usize>,
+    matcher: Matcher,
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    Literal(String),
+    Regex(Regex),
+}
+
+#[derive(Error, Debug)]
+pub enum IgnoreError {
+    #[error("could not read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid ignore pattern \"{pattern}\": {source}")]
+    Regex {
+        pattern: String,
+        source: regex::Error,
+    },
+}
+
+impl IgnoreList {
+    pub fn load(path: &Path) -> Result<Self, IgnoreError> {
+        let content = std::fs::read_to_string(path).map_err(|source| IgnoreError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        let mut list = Self::default();
+        for (i, line) in content.lines().enumerate() {
+            list.add(line, Some(path), Some(i + 1))?;
+        }
+        Ok(list)
+    }
+
+    /// Add one line of an ignore file, blank lines and comments are skipped.
+    pub fn add(
+        &mut self,
+        line: &str,
+        file: Option<&Path>,
+        line_number: Option<usize>,
+    ) -> Result<(), IgnoreError> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(());
+        }
+
+        let (negated, pattern) = match line.strip_prefix('!') {
+            Some(pattern) => (true, pattern),
+            None => (false, line),
+        };
+        // `\#` and `\!` for keys that really start with those characters
+        let pattern = pattern
+            .strip_prefix('\\')
+            .filter(|rest| rest.starts_with(['#', '!']))
+            .unwrap_or(pattern);
+
+        let matcher = Matcher::new(pattern).map_err(|source| IgnoreError::Regex {
+            pattern: pattern.to_string(),
+            source,
+        })?;
+        let index = self.entries.len();
+        match &matcher {
+            Matcher::Literal(key) => {
+                self.literals.insert(key.clone(), index);
+            }
+            Matcher::Regex(_) => self.patterns.push(index),
+        }
+        self.entries.push(IgnoreEntry {
+            pattern: pattern.to_string(),
+            negated,
+            file: file.map(Path::to_path_buf),
+            line: line_number,
+            matcher,
+        });
+
+        Ok(())
+    }
+
+    pub fn is_ignored(&self, key: &str) -> bool {
+        let literal = self.literals.get(key).copied();
+        let pattern = self
+            .patterns
+            .iter()
+            .rev()
+            .find(|&&i| self.entries[i].matches(key))
+            .copied();
+
+        match literal.max(pattern) {
+            Some(i) => !self.entries[i].negated,
+            None => false,
+        }
+    }
+
+    /// Entries that do not match any of `keys`, and can be removed.
+    pub fn stale_entries<'a>(&'a self, keys: &[&str]) -> Vec<&'a IgnoreEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| !keys.iter().any(|key| entry.matches(key)))
+            .collect()
+    }
+
+    pub fn entries(&self) -> &[IgnoreEntry] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl IgnoreEntry {
+    pub fn matches(&self, key: &str) -> bool {
+        match &self.matcher {
+            Matcher::Literal(literal) => literal == key,
+            Matcher::Regex(regex) => regex.is_match(key),
+        }
+    }
+}
+
+impl Matcher {
+    fn new(pattern: &str) -> Result<Self, regex::Error> {
+        // /regex/
+        if let Some(regex) = pattern
+            .strip_prefix('/')
+            .and_then(|rest| rest.strip_suffix('/'))
+            .filter(|regex| !regex.is_empty())
+        {
+            return Ok(Matcher::Regex(Regex::new(regex)?));
+        }
+
+        // Glob, `*` matches any number of characters and `?` exactly one
+        if pattern.contains(['*', '?']) {
+            let regex = pattern
+                .split('*')
+                .map(|part| {
+                    part.split('?')
+                        .map(regex::escape)
+                        .collect::<Vec<_>>()
+                        .join(".")
+                })
+                .collect::<Vec<_>>()
+                .join(".*");
+            return Ok(Matcher::Regex(Regex::new(&format!("^{}$", regex))?));
+        }
+
+        Ok(Matcher::Literal(pattern.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ignore_list(content: &str) -> IgnoreList {
+        let mut list = IgnoreList::default();
+        for (i, line) in content.lines().enumerate() {
+            list.add(line, None, Some(i + 1)).unwrap();
+        }
+        list
+    }
+
+    #[test]
+    fn test_patterns() {
+        let list = ignore_list(
+            r"
+            # Keys used by the API
+            errors.api.*
+            !errors.api.internal
+            /^enum\.status\.\d+$/
+            common.save
+            \#hashtag
+            ",
+        );
+
+        assert!(list.is_ignored("errors.api.not_found"));
+        assert!(list.is_ignored("errors.api.internal.details"));
+        assert!(!list.is_ignored("errors.api.internal"));
+        assert!(list.is_ignored("enum.status.404"));
+        assert!(!list.is_ignored("enum.status.unknown"));
+        assert!(list.is_ignored("common.save"));
+        assert!(list.is_ignored("#hashtag"));
+        assert!(!list.is_ignored("# Keys used by the API"));
+        assert_eq!(5, list.entries().len());
+    }
+
+    #[test]
+    fn test_last_match_wins() {
+        let list = ignore_list("!errors.api.internal\nerrors.api.*");
+        assert!(list.is_ignored("errors.api.internal"));
+    }
+
+    #[test]
+    fn test_stale_entries() {
+        let list = ignore_list("errors.api.*\ncommon.removed\n/^enum\\./");
+        let stale = list.stale_entries(&["errors.api.timeout", "common.save"]);
+
+        let stale = stale
+            .iter()
+            .map(|entry| (entry.pattern.as_str(), entry.line))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![("common.removed", Some(2)), ("/^enum\\./", Some(3))],
+            stale
+        );
+    }
+
+    #[test]
+    fn test_invalid_regex() {
+        let mut list = IgnoreList::default();
+        assert!(list.add("/(unclosed/", None, None).is_err());
+    }
+}
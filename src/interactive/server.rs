@@ -1,7 +1,4 @@
-use std::{
-    path::Path,
-    sync::{Arc, Mutex},
-};
+use std::sync::{Arc, Mutex};
 
 use anyhow::{Context, Result};
 use axum::{
@@ -18,12 +15,13 @@ use crate::{
         delete_translation, edit_translation_value, insert_translation, search_translations_keys,
         search_translations_values, translations,
     },
-    translation_file::TranslationFile,
+    locale::LocaleSet,
+    lock::TranslationLock,
 };
 
 pub struct AppState {
-    pub en_translation_file: Mutex<TranslationFile>,
-    pub sv_translation_file: Mutex<TranslationFile>,
+    pub locales: Mutex<LocaleSet>,
+    pub lock: Mutex<TranslationLock>,
 }
 
 static HTMX_FILE: &str = include_str!("../../assets/scripts/htmx_1.9.4.js");
@@ -31,7 +29,7 @@ static CSS_FILE: &str = include_str!("../../assets/main.css");
 static FAV_ICON: &[u8] = include_bytes!("../../assets/favicon.ico");
 
 // https://joeymckenzie.tech/blog/templates-with-rust-axum-htmx-askama/
-pub async fn run_server(en_path: &Path, sv_path: &Path) -> Result<()> {
+pub async fn run_server(locales: LocaleSet, lock: TranslationLock) -> Result<()> {
     // let env_filter = EnvFilter::from("info,kobo_sync=debug,tower_http=debug,axum=debug");
     // tracing_subscriber::fmt().with_env_filter(env_filter).init();
     tracing_subscriber::registry()
@@ -42,14 +40,9 @@ pub async fn run_server(en_path: &Path, sv_path: &Path) -> Result<()> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    let en_translation_file =
-        TranslationFile::new(en_path.to_path_buf()).expect("failed to open en translation file");
-    let sv_translation_file =
-        TranslationFile::new(sv_path.to_path_buf()).expect("failed to open sv translation file");
-
     let app_state = Arc::new(AppState {
-        en_translation_file: Mutex::new(en_translation_file),
-        sv_translation_file: Mutex::new(sv_translation_file),
+        locales: Mutex::new(locales),
+        lock: Mutex::new(lock),
     });
 
     let port = 3333_u16;
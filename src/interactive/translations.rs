@@ -1,4 +1,4 @@
-use std::{collections::BTreeMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc};
 
 use askama::Template;
 use axum::{
@@ -10,43 +10,54 @@ use axum::{
 use serde::Deserialize;
 
 use super::server::AppState;
+use crate::locale::LocaleSet;
 
 struct TranslationRow {
     key: String,
-    en: String,
-    sv: String,
+    values: Vec<TranslationValue>,
+}
+
+struct TranslationValue {
+    locale: String,
+    value: String,
+}
+
+fn get_translation_row(key: &str, locales: &LocaleSet) -> TranslationRow {
+    TranslationRow {
+        key: key.to_string(),
+        values: locales
+            .iter()
+            .map(|locale| TranslationValue {
+                locale: locale.name.clone(),
+                value: locale.file.entries.get(key).cloned().unwrap_or_default(),
+            })
+            .collect(),
+    }
 }
 
 // Translations root page
 #[derive(Template)]
 #[template(path = "pages/translations.html")]
 struct TranslationsTemplate {
+    locales: Vec<String>,
     translations: Vec<TranslationRow>,
 }
 
 pub async fn translations(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    fn get_translation_row(
-        key: &str,
-        en: &BTreeMap<String, String>,
-        sv: &BTreeMap<String, String>,
-    ) -> TranslationRow {
-        TranslationRow {
-            key: key.to_string(),
-            en: en.get(key).unwrap_or(&"".to_string()).to_string(),
-            sv: sv.get(key).unwrap_or(&"".to_string()).to_string(),
-        }
-    }
+    let locales = state.locales.lock().unwrap();
 
-    let en_file = state.en_translation_file.lock().unwrap();
-    let sv_file = state.sv_translation_file.lock().unwrap();
-
-    let rows = en_file
+    let rows = locales
+        .reference()
+        .file
         .entries
         .keys()
-        .map(|key| get_translation_row(key, &en_file.entries, &sv_file.entries))
+        .map(|key| get_translation_row(key, &locales))
         .collect::<Vec<TranslationRow>>();
 
-    TranslationsTemplate { translations: rows }
+    TranslationsTemplate {
+        locales: locales.names(),
+        translations: rows,
+    }
 }
 
 // Translations list
@@ -67,27 +78,21 @@ pub async fn search_translations_keys(
 ) -> impl IntoResponse {
     let query = query.query.unwrap_or_default().to_ascii_lowercase();
 
-    fn filter_by_query(
-        en_entries: &BTreeMap<String, String>,
-        sv_entries: &BTreeMap<String, String>,
-        query: &str,
-    ) -> Vec<TranslationRow> {
-        en_entries
-            .iter()
-            .filter(|(key, _)| key.to_ascii_lowercase().contains(query))
-            .map(|(key, _)| TranslationRow {
-                key: key.to_string(),
-                en: en_entries.get(key).unwrap_or(&"".to_string()).to_string(),
-                sv: sv_entries.get(key).unwrap_or(&"".to_string()).to_string(),
-            })
+    fn filter_by_query(locales: &LocaleSet, query: &str) -> Vec<TranslationRow> {
+        locales
+            .reference()
+            .file
+            .entries
+            .keys()
+            .filter(|key| key.to_ascii_lowercase().contains(query))
+            .map(|key| get_translation_row(key, locales))
             .collect::<Vec<TranslationRow>>()
     }
 
-    let en_file = state.en_translation_file.lock().unwrap();
-    let sv_file = state.sv_translation_file.lock().unwrap();
+    let locales = state.locales.lock().unwrap();
 
     TranslationsList {
-        translations: filter_by_query(&en_file.entries, &sv_file.entries, &query),
+        translations: filter_by_query(&locales, &query),
     }
 }
 
@@ -97,30 +102,27 @@ pub async fn search_translations_values(
 ) -> impl IntoResponse {
     let query = query.query.unwrap_or_default().to_ascii_lowercase();
 
-    fn filter_by_query(
-        en_entries: &BTreeMap<String, String>,
-        sv_entries: &BTreeMap<String, String>,
-        query: &str,
-    ) -> Vec<TranslationRow> {
-        let combined_rows_iter = en_entries.iter().map(|(key, value)| TranslationRow {
-            key: key.to_string(),
-            en: value.to_string(),
-            sv: sv_entries.get(key).unwrap_or(&"".to_string()).to_string(),
-        });
+    fn filter_by_query(locales: &LocaleSet, query: &str) -> Vec<TranslationRow> {
+        let combined_rows_iter = locales
+            .reference()
+            .file
+            .entries
+            .keys()
+            .map(|key| get_translation_row(key, locales));
 
         combined_rows_iter
             .filter(|row| {
-                row.en.to_ascii_lowercase().contains(query)
-                    || row.sv.to_ascii_lowercase().contains(query)
+                row.values
+                    .iter()
+                    .any(|value| value.value.to_ascii_lowercase().contains(query))
             })
             .collect()
     }
 
-    let en_file = state.en_translation_file.lock().unwrap();
-    let sv_file = state.sv_translation_file.lock().unwrap();
+    let locales = state.locales.lock().unwrap();
 
     TranslationsList {
-        translations: filter_by_query(&en_file.entries, &sv_file.entries, &query),
+        translations: filter_by_query(&locales, &query),
     }
 }
 
@@ -135,88 +137,93 @@ pub async fn edit_translation_value(
     State(state): State<Arc<AppState>>,
     Form(query): Form<TranslationValueEdit>,
 ) -> impl IntoResponse {
-    let mut en_file = state.en_translation_file.lock().unwrap();
-    let mut sv_file = state.sv_translation_file.lock().unwrap();
-
-    match query.language.as_str() {
-        "en" => {
-            en_file.entries.insert(query.key, query.value);
-            en_file
-                .write()
-                .expect("failed to write en translation file");
+    let mut locales = state.locales.lock().unwrap();
+    let reference = locales.reference().name.clone();
+    let source = locales
+        .reference()
+        .file
+        .entries
+        .get(&query.key)
+        .cloned()
+        .unwrap_or_default();
+
+    match locales.get_mut(&query.language) {
+        Some(locale) => {
+            locale
+                .file
+                .entries
+                .insert(query.key.clone(), query.value.clone());
+            if locale.file.write().is_err() {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "failed to write translation file",
+                );
+            }
         }
-        "sv" => {
-            sv_file.entries.insert(query.key, query.value);
-            sv_file
-                .write()
-                .expect("failed to write sv translation file");
+        None => return (StatusCode::BAD_REQUEST, "invalid language"),
+    }
+
+    // Saving a translation means it is up to date with the reference value
+    if query.language != reference {
+        let mut lock = state.lock.lock().unwrap();
+        lock.record(&query.language, &query.key, &source, &query.value);
+        if lock.save().is_err() {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to write lock file",
+            );
         }
-        _ => return (StatusCode::BAD_REQUEST, "invalid language"),
     }
 
     (StatusCode::OK, "ok")
 }
 
-#[derive(Deserialize)]
-pub struct TranslationInsert {
-    key: String,
-    en: String,
-    sv: String,
-}
-
+/// The key and one value per locale, the form fields are named after the locales.
 pub async fn insert_translation(
     State(state): State<Arc<AppState>>,
-    Form(query): Form<TranslationInsert>,
-) -> impl IntoResponse {
-    let mut en_translation_file = state.en_translation_file.lock().unwrap();
-    let mut sv_translation_file = state.sv_translation_file.lock().unwrap();
+    Form(mut query): Form<HashMap<String, String>>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let mut locales = state.locales.lock().unwrap();
+    let key = query.remove("key").unwrap_or_default();
 
-    // if en_translation_file.entries.get(&query.key).is_some() {
+    // if locales.reference().file.entries.get(&key).is_some() {
     //     return (StatusCode::BAD_REQUEST, "key already exists");
     // }
 
-    en_translation_file
-        .entries
-        .insert(query.key.clone(), query.en.clone());
-    en_translation_file
-        .write()
-        .expect("failed to write en translation file");
+    for locale in locales.iter_mut() {
+        // Values are prefixed, so that a locale can be named "key"
+        let value = query
+            .remove(&format!("value.{}", locale.name))
+            .unwrap_or_default();
+        locale.file.entries.insert(key.clone(), value);
+        locale
+            .file
+            .write()
+            .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+    }
 
-    sv_translation_file
-        .entries
-        .insert(query.key.clone(), query.sv.clone());
-    sv_translation_file
-        .write()
-        .expect("failed to write sv translation file");
-
-    let new_translation_row = TranslationRow {
-        key: query.key,
-        en: query.en,
-        sv: query.sv,
-    };
-
-    let mut translations = en_translation_file
+    let mut lock = state.lock.lock().unwrap();
+    let source = &locales.reference().file.entries[&key];
+    for locale in locales.others() {
+        lock.record(&locale.name, &key, source, &locale.file.entries[&key]);
+    }
+    lock.save()
+        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+
+    let new_translation_row = get_translation_row(&key, &locales);
+
+    let mut translations = locales
+        .reference()
+        .file
         .entries
         .keys()
-        .filter(|key| key != &&new_translation_row.key)
-        .map(|key| TranslationRow {
-            key: key.to_string(),
-            en: en_translation_file
-                .entries
-                .get(key)
-                .unwrap_or(&"".to_string())
-                .to_string(),
-            sv: sv_translation_file
-                .entries
-                .get(key)
-                .unwrap_or(&"".to_string())
-                .to_string(),
-        })
+        .filter(|other| other != &&new_translation_row.key)
+        .map(|key| get_translation_row(key, &locales))
         .collect::<Vec<TranslationRow>>();
 
     translations.insert(0, new_translation_row);
 
-    TranslationsList { translations }
+    Ok(TranslationsList { translations })
 }
 
 #[derive(Deserialize)]
@@ -224,44 +231,32 @@ pub struct TranslationDelete {
     key: String,
 }
 
-#[derive(Template)]
-#[template(path = "icons/delete.html")]
-struct DeleteIcon;
-
 pub async fn delete_translation(
     State(state): State<Arc<AppState>>,
     Form(query): Form<TranslationDelete>,
-) -> impl IntoResponse {
-    let mut en_translation_file = state.en_translation_file.lock().unwrap();
-    let mut sv_translation_file = state.sv_translation_file.lock().unwrap();
-
-    en_translation_file.entries.remove(&query.key);
-    en_translation_file
-        .write()
-        .expect("failed to write en translation file");
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let mut locales = state.locales.lock().unwrap();
+
+    for locale in locales.iter_mut() {
+        locale.file.entries.remove(&query.key);
+        locale
+            .file
+            .write()
+            .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+    }
 
-    sv_translation_file.entries.remove(&query.key);
-    sv_translation_file
-        .write()
-        .expect("failed to write sv translation file");
+    let mut lock = state.lock.lock().unwrap();
+    lock.remove(&query.key);
+    lock.save()
+        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
 
-    let translations = en_translation_file
+    let translations = locales
+        .reference()
+        .file
         .entries
         .keys()
-        .map(|key| TranslationRow {
-            key: key.to_string(),
-            en: en_translation_file
-                .entries
-                .get(key)
-                .unwrap_or(&"".to_string())
-                .to_string(),
-            sv: sv_translation_file
-                .entries
-                .get(key)
-                .unwrap_or(&"".to_string())
-                .to_string(),
-        })
+        .map(|key| get_translation_row(key, &locales))
         .collect::<Vec<TranslationRow>>();
 
-    TranslationsList { translations }
+    Ok(TranslationsList { translations })
 }
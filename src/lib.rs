@@ -1,14 +1,20 @@
-use nom::{
-    bytes::complete::{tag, take_until},
-    combinator::map,
-    sequence::tuple,
-    IResult,
-};
-
+pub mod baseline;
+pub mod changes;
+pub mod check;
+pub mod config;
+pub mod diff;
+pub mod export;
+pub mod fix;
+pub mod git;
+pub mod ignore;
 pub mod interactive;
+pub mod locale;
+pub mod lock;
+pub mod merge;
+pub mod report;
+pub mod scan;
+pub mod stats;
 pub mod translation_file;
 pub mod ts_file;
-
-fn fenced<'a>(start: &'a str, end: &'a str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
-    map(tuple((tag(start), take_until(end), tag(end))), |x| x.1)
-}
+pub mod usage_index;
+pub mod watch;
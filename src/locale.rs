@@ -0,0 +1,250 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use thiserror::Error;
+
+use crate::translation_file::{TranslationFile, TranslationFileError};
+
+/// Where to find the translation file of a locale, written as `<name>=<path>` on the command line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocaleSpec {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// A locale together with its loaded translation file.
+#[derive(Debug, Clone)]
+pub struct Locale {
+    pub name: String,
+    pub file: TranslationFile,
+}
+
+/// All locales of a project. The first locale is the reference that every other locale is
+/// checked against, and whose keys are matched against the usages in the code.
+#[derive(Debug, Clone)]
+pub struct LocaleSet {
+    locales: Vec<Locale>,
+}
+
+#[derive(Error, Debug)]
+pub enum LocaleError {
+    #[error("no translation files given")]
+    NoLocales,
+    #[error("locale \"{0}\" is given more than once")]
+    DuplicateLocale(String),
+    #[error("reference locale \"{0}\" is not one of the given locales")]
+    UnknownReference(String),
+    #[error("could not read locales directory {path}: {source}")]
+    Discover {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error(transparent)]
+    TranslationFile(#[from] TranslationFileError),
+}
+
+impl FromStr for LocaleSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((name, path)) if !name.trim().is_empty() && !path.trim().is_empty() => Ok(Self {
+                name: name.trim().to_string(),
+                path: PathBuf::from(path.trim()),
+            }),
+            _ => Err(format!("expected <name>=<path>, got \"{}\"", s)),
+        }
+    }
+}
+
+impl fmt::Display for LocaleSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.name, self.path.display())
+    }
+}
+
+impl LocaleSpec {
+    /// Find all `<locale>.json` files in `dir`, sorted by locale name.
+    pub fn discover(dir: &Path) -> Result<Vec<Self>, LocaleError> {
+        let read_dir = std::fs::read_dir(dir).map_err(|source| LocaleError::Discover {
+            path: dir.to_path_buf(),
+            source,
+        })?;
+
+        let mut specs = read_dir
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| {
+                let name = path.file_stem()?.to_str()?.to_string();
+                Some(Self { name, path })
+            })
+            .collect::<Vec<_>>();
+        specs.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(specs)
+    }
+}
+
+impl LocaleSet {
+    /// Load the translation files of all locales.
+    ///
+    /// The reference locale defaults to `en` if present, otherwise the first given locale.
+    pub fn load(specs: Vec<LocaleSpec>, reference: Option<&str>) -> Result<Self, LocaleError> {
+        if specs.is_empty() {
+            return Err(LocaleError::NoLocales);
+        }
+
+        for (i, spec) in specs.iter().enumerate() {
+            if specs[..i].iter().any(|other| other.name == spec.name) {
+                return Err(LocaleError::DuplicateLocale(spec.name.clone()));
+            }
+        }
+
+        let reference_index = match reference {
+            Some(reference) => specs
+                .iter()
+                .position(|spec| spec.name == reference)
+                .ok_or_else(|| LocaleError::UnknownReference(reference.to_string()))?,
+            None => specs.iter().position(|spec| spec.name == "en").unwrap_or(0),
+        };
+
+        let mut locales = specs
+            .into_iter()
+            .map(|spec| {
+                Ok(Locale {
+                    file: TranslationFile::new(spec.path)?,
+                    name: spec.name,
+                })
+            })
+            .collect::<Result<Vec<_>, TranslationFileError>>()?;
+        let reference = locales.remove(reference_index);
+        locales.insert(0, reference);
+
+        Ok(Self { locales })
+    }
+
+    pub fn reference(&self) -> &Locale {
+        &self.locales[0]
+    }
+
+    /// All locales except the reference.
+    pub fn others(&self) -> impl Iterator<Item = &Locale> {
+        self.locales.iter().skip(1)
+    }
+
+    /// All locales, starting with the reference.
+    pub fn iter(&self) -> impl Iterator<Item = &Locale> {
+        self.locales.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Locale> {
+        self.locales.iter_mut()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Locale> {
+        self.locales.iter().find(|locale| locale.name == name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Locale> {
+        self.locales.iter_mut().find(|locale| locale.name == name)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.locales
+            .iter()
+            .map(|locale| locale.name.clone())
+            .collect()
+    }
+
+    /// Check every locale against the reference locale.
+    ///
+    /// The locales are compatible if:
+    /// - They all have the same keys as the reference
+    /// - All keys have a non-empty value
+    /// - All values are valid ICU messages
+    /// - All values use the same arguments and tags as the reference
+    pub fn is_compatible(&self) -> Result<(), Vec<TranslationFileError>> {
+        let reference = &self.reference().file;
+        let mut errors = Vec::new();
+
+        for other in self.others() {
+            errors.extend(reference.find_missing_keys(&other.file));
+            errors.extend(other.file.find_missing_keys(reference));
+            errors.extend(other.file.find_placeholder_mismatches(reference));
+        }
+        for locale in self.iter() {
+            errors.extend(locale.file.find_empty_values());
+            errors.extend(locale.file.find_invalid_messages());
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(())
+    }
+
+    /// Write all translation files back to disk.
+    pub fn write(&self) -> anyhow::Result<()> {
+        for locale in self.iter() {
+            locale.file.write()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl LocaleSet {
+    /// The `en` and `sv` files of `test_files/check`, for tests.
+    pub fn test() -> Self {
+        let specs = vec![
+            "en=test_files/check/en.json".parse().unwrap(),
+            "sv=test_files/check/sv.json".parse().unwrap(),
+        ];
+        Self::load(specs, None).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_locale_spec() {
+        let actual: LocaleSpec = "no=./translations/no.json".parse().unwrap();
+        let expected = LocaleSpec {
+            name: "no".to_string(),
+            path: PathBuf::from("./translations/no.json"),
+        };
+        assert_eq!(expected, actual);
+
+        assert!("./translations/no.json".parse::<LocaleSpec>().is_err());
+        assert!("no=".parse::<LocaleSpec>().is_err());
+    }
+
+    #[test]
+    fn test_reference_defaults_to_en() {
+        let specs = vec![
+            "sv=test_files/sv.json".parse().unwrap(),
+            "en=test_files/en.json".parse().unwrap(),
+        ];
+        let locales = LocaleSet::load(specs, None).unwrap();
+        assert_eq!("en", locales.reference().name);
+        assert_eq!(vec!["en", "sv"], locales.names());
+    }
+
+    #[test]
+    fn test_discover_locales() {
+        let specs = LocaleSpec::discover(Path::new("test_files")).unwrap();
+        let names = specs
+            .iter()
+            .map(|spec| spec.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["en", "sv"], names);
+    }
+}
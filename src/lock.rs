@@ -0,0 +1,221 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::{locale::LocaleSet, translation_file::TranslationFileError};
+
+/// Name of the lock file, next to the translation file of the reference locale by default.
+pub static LOCK_FILE_NAME: &str = "ramilang.lock";
+
+/// Which reference value each translation was made from, so that translations of reference
+/// values that changed since can be found.
+///
+/// Only fingerprints of the values are stored. A translation is stale when the reference value
+/// changed but the translation did not, and is up to date again once it is changed or saved in
+/// the edit mode.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TranslationLock {
+    #[serde(skip)]
+    path: PathBuf,
+    /// The entry of each key, per locale
+    locales: BTreeMap<String, BTreeMap<String, LockEntry>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct LockEntry {
+    /// Fingerprint of the reference value that was translated
+    source: String,
+    /// Fingerprint of the translation
+    value: String,
+}
+
+#[derive(Error, Debug)]
+pub enum LockError {
+    #[error("could not read {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid lock file {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("could not write {path}: {source}")]
+    Write {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+impl TranslationLock {
+    /// Load the lock file at `path`, a lock file that does not exist yet is empty.
+    pub fn load(path: &Path) -> Result<Self, LockError> {
+        let mut lock = match std::fs::read_to_string(path) {
+            Ok(content) => {
+                serde_json::from_str::<Self>(&content).map_err(|source| LockError::Parse {
+                    path: path.to_path_buf(),
+                    source,
+                })?
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(source) => {
+                return Err(LockError::Read {
+                    path: path.to_path_buf(),
+                    source,
+                })
+            }
+        };
+        lock.path = path.to_path_buf();
+        Ok(lock)
+    }
+
+    /// The lock file next to the translation file of the reference locale.
+    pub fn default_path(locales: &LocaleSet) -> PathBuf {
+        locales.reference().file.path.with_file_name(LOCK_FILE_NAME)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Record that `value` of `locale` is a translation of the reference value `source`.
+    pub fn record(&mut self, locale: &str, key: &str, source: &str, value: &str) {
+        self.locales.entry(locale.to_string()).or_default().insert(
+            key.to_string(),
+            LockEntry {
+                source: fingerprint(source),
+                value: fingerprint(value),
+            },
+        );
+    }
+
+    /// Forget `key` in every locale.
+    pub fn remove(&mut self, key: &str) {
+        for entries in self.locales.values_mut() {
+            entries.remove(key);
+        }
+    }
+
+    /// Record the translations that are new or were changed since they were recorded, and
+    /// forget keys and locales that no longer exist. Stale translations are left as they are.
+    pub fn update(&mut self, locales: &LocaleSet) {
+        let reference = &locales.reference().file.entries;
+        self.locales
+            .retain(|name, _| locales.others().any(|locale| &locale.name == name));
+
+        for locale in locales.others() {
+            let entries = self.locales.entry(locale.name.clone()).or_default();
+            entries.retain(|key, _| locale.file.entries.contains_key(key));
+
+            for (key, value) in &locale.file.entries {
+                let Some(source) = reference.get(key) else {
+                    continue;
+                };
+                if value.is_empty() {
+                    continue;
+                }
+                let value_fingerprint = fingerprint(value);
+                if entries
+                    .get(key)
+                    .is_none_or(|entry| entry.value != value_fingerprint)
+                {
+                    entries.insert(
+                        key.clone(),
+                        LockEntry {
+                            source: fingerprint(source),
+                            value: value_fingerprint,
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    /// Translations whose reference value changed since they were recorded.
+    pub fn find_stale_translations(&self, locales: &LocaleSet) -> Vec<TranslationFileError> {
+        let reference = &locales.reference().file;
+        let mut errors = Vec::new();
+        for locale in locales.others() {
+            let Some(entries) = self.locales.get(&locale.name) else {
+                continue;
+            };
+            for (key, entry) in entries {
+                let (Some(source), Some(value)) =
+                    (reference.entries.get(key), locale.file.entries.get(key))
+                else {
+                    continue;
+                };
+                if entry.value == fingerprint(value) && entry.source != fingerprint(source) {
+                    errors.push(TranslationFileError::StaleTranslation {
+                        key: key.clone(),
+                        path: locale.file.path.clone(),
+                        reference: reference.path.clone(),
+                    });
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// Write the lock file, unless it would not change.
+    pub fn save(&self) -> Result<(), LockError> {
+        let write_error = |source| LockError::Write {
+            path: self.path.clone(),
+            source,
+        };
+        let mut content = serde_json::to_string_pretty(self)
+            .map_err(|err| write_error(std::io::Error::other(err)))?;
+        content.push('\n');
+
+        if std::fs::read_to_string(&self.path).is_ok_and(|existing| existing == content) {
+            return Ok(());
+        }
+        std::fs::write(&self.path, content).map_err(write_error)
+    }
+}
+
+/// A short SHA-256 of a value, enough to notice that it changed.
+fn fingerprint(value: &str) -> String {
+    format!("{:x}", Sha256::digest(value.as_bytes()))[..16].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stale_translations() {
+        let mut locales = LocaleSet::test();
+        let mut lock = TranslationLock::default();
+        lock.update(&locales);
+        assert!(lock.find_stale_translations(&locales).is_empty());
+
+        let en = &mut locales.get_mut("en").unwrap().file.entries;
+        en.insert("common.save".to_string(), "Save changes".to_string());
+        en.insert("common.unused".to_string(), "Not used".to_string());
+        lock.update(&locales);
+        let stale = lock
+            .find_stale_translations(&locales)
+            .into_iter()
+            .map(|error| match error {
+                TranslationFileError::StaleTranslation { key, .. } => key,
+                error => panic!("unexpected error {}", error),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["common.save", "common.unused"], stale);
+
+        // Changing the translation, or saving it in the edit mode, makes it up to date again
+        let sv = &mut locales.get_mut("sv").unwrap().file.entries;
+        sv.insert("common.save".to_string(), "Spara ändringar".to_string());
+        lock.update(&locales);
+        lock.record("sv", "common.unused", "Not used", "Oanvänd");
+        assert!(lock.find_stale_translations(&locales).is_empty());
+    }
+}
@@ -1,193 +1,675 @@
-use std::path::PathBuf;
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
 
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
 use console::style;
 use ramilang::{
+    baseline::Baseline,
+    changes::{ChangeBase, Changes},
+    check,
+    config::{Config, SourceFilter},
+    diff::{self, DiffFormat, TranslationDiff},
+    export::{self, ExportFormat},
+    fix::{self, MissingValue},
+    git::Repository,
+    ignore::IgnoreList,
     interactive,
-    translation_file::{TranslationFile, TranslationFileError},
-    ts_file::{KeyUsage, TSFile},
+    locale::{LocaleError, LocaleSet, LocaleSpec},
+    lock::TranslationLock,
+    merge,
+    report::{self, Diagnostic, Format, Level, Rule, Severity},
+    scan::{self, UsageCache},
+    stats::{self, Stats},
+    translation_file::Shape,
+    ts_file::TSFileError,
+    usage_index::UsageIndex,
 };
-use walkdir::{DirEntry, WalkDir};
 
 /// Handle those damn translations...
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
-struct Args {
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    options: Options,
+    /// Arguments of `check`, which runs when no command is given
+    #[command(flatten)]
+    check: CheckArgs,
+    /// Sort keys in translation files once the checks pass, kept for existing scripts
+    #[arg(long, action, hide = true)]
+    sort: bool,
+    /// Interactive mode once the checks pass, kept for existing scripts
+    #[arg(long, short, action, hide = true)]
+    interactive: bool,
+    /// Fix the translation files before the checks, kept for existing scripts
+    #[arg(long, action, hide = true)]
+    fix: bool,
+    #[arg(long, value_enum, default_value_t = MissingValue::Todo, hide = true, requires = "fix")]
+    missing: MissingValue,
+    #[arg(long, action, hide = true, requires = "fix")]
+    dry_run: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check the translation files and the keys used in the code (the default)
+    Check(CheckArgs),
+    /// Sort the keys of the translation files
+    Sort {
+        /// Convert the translation files to flat or nested keys as well
+        #[arg(long, value_enum, value_name = "SHAPE")]
+        convert: Option<Shape>,
+    },
+    /// Edit the translations in the browser, even if the checks fail
+    Edit,
+    /// Remove unused keys from all translation files and add the keys that some of them lack
+    Fix(FixArgs),
+    /// Show the number of keys, translations and usages
+    Stats {
+        /// Print the statistics as JSON
+        #[arg(long, action)]
+        json: bool,
+    },
+    /// Export the translations of all locales, for example for translators
+    Export {
+        #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+        /// File to write to (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Only export the keys that some locale lacks or has an empty value for
+        #[arg(long, action)]
+        untranslated: bool,
+    },
+    /// Show the keys that were added, removed, renamed or changed between two versions of the
+    /// translation files
+    Diff(DiffArgs),
+    /// Merge two versions of a translation file key by key, as a git merge driver:
+    /// `ramilang merge-driver %O %A %B`
+    MergeDriver {
+        /// The common ancestor (%O)
+        base: PathBuf,
+        /// Our version (%A), which the result is written to
+        ours: PathBuf,
+        /// Their version (%B)
+        theirs: PathBuf,
+    },
+}
+
+/// Options shared by all commands.
+#[derive(Args, Debug)]
+struct Options {
+    /// Config file (default: ramilang.toml, or the "ramilang" section of package.json, in the
+    /// current directory or any parent)
+    #[arg(short, long, global = true)]
+    config: Option<PathBuf>,
     /// Root directory to search from
-    #[arg(short, long, default_value = ".")]
-    root_dir: PathBuf,
-    /// Path to English translation file
-    #[arg(short, long)]
-    en_file: PathBuf,
-    /// Path to Swedish translation file
-    #[arg(short, long)]
-    sv_file: PathBuf,
+    #[arg(short, long, global = true)]
+    root_dir: Option<PathBuf>,
+    /// Extension of the files to search, can be repeated (default: ts, tsx)
+    #[arg(long = "extension", value_name = "EXTENSION", global = true)]
+    extensions: Vec<String>,
+    /// Only search files matching this glob, can be repeated
+    #[arg(long, global = true)]
+    include: Vec<String>,
+    /// Never search files or directories matching this glob, can be repeated (default:
+    /// **/node_modules)
+    #[arg(long, global = true)]
+    exclude: Vec<String>,
+    /// Translation file of a locale as <NAME>=<PATH>, can be repeated
+    #[arg(short, long = "locale", value_name = "NAME=PATH", global = true)]
+    locales: Vec<LocaleSpec>,
+    /// Directory with one <locale>.json translation file per locale
+    #[arg(long, global = true)]
+    locales_dir: Option<PathBuf>,
+    /// Locale that all other locales are checked against (default: en, or the first locale)
+    #[arg(long, global = true)]
+    reference: Option<String>,
+    /// Path to English translation file, same as `--locale en=<PATH>`
+    #[arg(short, long, global = true)]
+    en_file: Option<PathBuf>,
+    /// Path to Swedish translation file, same as `--locale sv=<PATH>`
+    #[arg(short, long, global = true)]
+    sv_file: Option<PathBuf>,
     /// Path to key ignore unused file
-    #[arg(long)]
+    #[arg(long, global = true)]
     ignore_file: Option<PathBuf>,
-    /// Sort keys in translation files
-    #[arg(long, action)]
-    sort: bool,
-    /// Interactive mode
+    /// Parse every source file, instead of reusing the key usages of unchanged files
+    #[arg(long, action, global = true)]
+    no_cache: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+struct CheckArgs {
+    /// Output format of the problems found
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+    /// Keep running and check again whenever a translation file or source file changes
     #[arg(long, short, action)]
-    interactive: bool,
+    watch: bool,
+    /// Severity of a rule as <RULE>=<off|warning|error>, can be repeated
+    #[arg(long = "severity", value_name = "RULE=LEVEL", value_parser = parse_severity)]
+    severities: Vec<(Rule, Level)>,
+    /// Write the problems found to the baseline file, so that later checks only fail on new
+    /// problems
+    #[arg(long, action, conflicts_with_all = ["since", "staged"])]
+    update_baseline: bool,
+    /// Record the translations that are new or were changed by hand in the lock file, which
+    /// `edit` and `fix` do as well
+    #[arg(long, action)]
+    update_lock: bool,
+    /// Only report problems in the source files and keys changed since the current branch
+    /// left this git ref, like `main`
+    #[arg(long, value_name = "REF", conflicts_with_all = ["staged", "watch"])]
+    since: Option<String>,
+    /// Only report problems in the source files and keys of the changes staged for commit
+    #[arg(long, action, conflicts_with = "watch")]
+    staged: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+struct FixArgs {
+    /// Value of the keys that are added
+    #[arg(long, value_enum, default_value_t = MissingValue::Todo)]
+    missing: MissingValue,
+    /// Show the changes as a diff instead of writing them
+    #[arg(long, action)]
+    dry_run: bool,
+    /// Remove unused keys even if some source files could not be read
+    #[arg(long, action)]
+    force: bool,
 }
 
-static EXTENSIONS_TO_SEARCH: [&str; 2] = ["ts", "tsx"];
+#[derive(Args, Debug, Clone)]
+struct DiffArgs {
+    /// Old version, a translation file or a directory with one <locale>.json file per locale
+    #[arg(requires = "new", required_unless_present = "git_ref")]
+    old: Option<PathBuf>,
+    /// New version, a translation file or a directory with one <locale>.json file per locale
+    new: Option<PathBuf>,
+    /// Compare the translation files with their version where the current branch left this git
+    /// ref, like `main`
+    #[arg(long = "ref", value_name = "REF", conflicts_with = "old")]
+    git_ref: Option<String>,
+    #[arg(long, value_enum, default_value_t = DiffFormat::Text)]
+    format: DiffFormat,
+}
 
 // clear; cargo run -- --sort --root-dir C:\Users\pbac\dev\SE-CustomerPortal\CustomerPortal\ --en-file C:\Users\pbac\dev\SE-CustomerPortal\CustomerPortal\shared\translations\en.json --sv-file C:\Users\pbac\dev\SE-CustomerPortal\CustomerPortal\shared\translations\sv.json --ignore-file C:\Users\pbac\dev\SE-CustomerPortal\CustomerPortal\shared\translations\.keyignore
 #[tokio::main]
 async fn main() {
-    let args = Args::parse();
+    let cli = Cli::parse();
+    let mut config = load_config(&cli.options).unwrap_or_else(|err| exit_with_error(err));
 
-    // Try to open the translation files
-    let en_translation_file = TranslationFile::new(args.en_file.clone());
-    let sv_translation_file = TranslationFile::new(args.sv_file.clone());
-
-    println!("\n{}\n", style("Checking translations...").blue().bold());
-    // Check for problems with the translation files
-    match (&en_translation_file, &sv_translation_file) {
-        (Err(err), _) | (_, Err(err)) => {
-            println!(
-                "{}{}",
-                style("ERROR").red().bold(),
-                style(format!(": {}", err)).bold()
-            );
-            std::process::exit(1);
-        }
-        (Ok(en_translation_file), Ok(sv_translation_file)) => {
-            if let Err((en_errors, sv_errors)) =
-                en_translation_file.is_compatible_with(sv_translation_file)
-            {
-                for error in en_errors.iter().chain(sv_errors.iter()) {
-                    match error {
-                        TranslationFileError::MissingKey { key, missing_in } => {
-                            println!(
-                                "{} key {} not found in {}",
-                                style("[MISSING]").yellow().bold(),
-                                style(key).bold(),
-                                style(missing_in.to_str().unwrap()).italic()
-                            );
-                        }
-                        TranslationFileError::EmptyValue(key) => {
-                            println!(
-                                "{} key {} seems to be empty",
-                                style("[EMPTY]").yellow().bold(),
-                                style(key).bold()
-                            );
-                        }
-                        _ => {}
-                    }
+    match cli.command {
+        None => {
+            // `--fix`, `--sort` and `-i` from before there were commands
+            if cli.fix {
+                let args = FixArgs {
+                    missing: cli.missing,
+                    dry_run: cli.dry_run,
+                    force: false,
+                };
+                fix(&config, &args);
+                if args.dry_run {
+                    return;
                 }
-
-                println!(
-                    "{}{}",
-                    style("ERROR").red().bold(),
-                    style(": translation files are not compatible, see problems above").bold()
-                );
-                std::process::exit(1);
+            }
+            let text = cli.check.format == Format::Text;
+            let mut locales = check(&mut config, &cli.check);
+            if cli.sort {
+                sort(&mut locales, None, text);
+            }
+            if cli.interactive {
+                edit(&config, locales).await;
             }
         }
-    }
-
-    // Test against all TS files in the root directory
-    let walker = WalkDir::new(args.root_dir)
-        .into_iter()
-        // Exclude node_modules
-        .filter_entry(|e| !is_node_modules(e))
-        // Filter out any non-accessible files
-        .filter_map(|e| e.ok());
-
-    let mut key_usages: Vec<KeyUsage> = Vec::new();
-    for file in walker.filter(|e| e.path().is_file()) {
-        if let Some(ext) = file.path().extension() {
-            if EXTENSIONS_TO_SEARCH.contains(&ext.to_str().unwrap()) {
-                let mut ts_file = TSFile::new(file.path());
-
-                // Collect KeyUsage from different methods
-                let formatted_message_keys = ts_file.find_formatted_message_usages();
-                let format_message_keys = ts_file.find_format_message_usages();
-                let misc_usages = ts_file.find_misc_usages();
-
-                // Extend the used_keys vector with the KeyUsages
-                key_usages.extend(format_message_keys);
-                key_usages.extend(formatted_message_keys);
-                key_usages.extend(misc_usages);
+        Some(Command::Check(args)) => {
+            check(&mut config, &args);
+        }
+        Some(Command::Sort { convert }) => sort(&mut load_locales(&config), convert, true),
+        Some(Command::Edit) => edit(&config, load_locales(&config)).await,
+        Some(Command::Fix(args)) => fix(&config, &args),
+        Some(Command::Stats { json }) => stats(&config, json),
+        Some(Command::Export {
+            format,
+            output,
+            untranslated,
+        }) => {
+            let exported = export::export(&load_locales(&config), format, untranslated);
+            match output {
+                Some(path) => std::fs::write(&path, exported)
+                    .unwrap_or_else(|err| exit_with_error(format!("{}: {}", path.display(), err))),
+                None => print!("{}", exported),
             }
         }
+        Some(Command::Diff(args)) => diff(&config, &args),
+        Some(Command::MergeDriver { base, ours, theirs }) => merge_driver(&base, &ours, &theirs),
+    }
+}
+
+/// Run all checks, so that every problem is reported at once. Exits if any of them fails, and
+/// never returns in watch mode.
+fn check(config: &mut Config, args: &CheckArgs) -> LocaleSet {
+    for (rule, level) in &args.severities {
+        config.severity.set(*rule, *level);
     }
+    let source_filter = config
+        .source_filter()
+        .unwrap_or_else(|err| exit_with_error(err));
+    let locales = load_locales(config);
 
-    // Check that all usages are valid
-    let mut n_invalid_usages = 0;
+    if args.format == Format::Text {
+        println!("\n{}\n", style("Checking translations...").blue().bold());
+    }
+
+    let source_files = scan::find_source_files(&config.roots, &source_filter);
+    let (key_usages, cache, _) = find_usages(config, &source_files);
+    let ignore = load_ignore_list(config);
+    let mut lock = load_lock(config, &locales);
+    if args.update_lock {
+        update_lock(&mut lock, &locales);
+    }
 
-    let entries = en_translation_file.as_ref().unwrap().entries.clone();
-    key_usages.iter().for_each(|usage| {
-        if !entries.contains_key(usage.key.as_str()) {
+    let diagnostics = check::run(
+        &locales,
+        &key_usages,
+        &ignore,
+        &config.allow_untranslated,
+        &lock,
+    );
+    let baseline = if args.update_baseline {
+        let baseline = Baseline::new(
+            &config.baseline_file,
+            &config.severity.apply(diagnostics.clone()),
+        );
+        baseline.save().unwrap_or_else(|err| exit_with_error(err));
+        if args.format == Format::Text {
             println!(
-                "{} key {} does not exist! {}",
-                style("[INVALID]").yellow().bold(),
-                style(usage.key.as_str()).bold(),
+                "{}",
                 style(format!(
-                    "({}:{})",
-                    usage.file_path.to_str().unwrap(),
-                    usage.line
+                    "Wrote {} problems to {}",
+                    baseline.count(),
+                    baseline.path().display()
                 ))
                 .italic()
-                .dim()
             );
-            n_invalid_usages += 1;
         }
-    });
+        baseline
+    } else {
+        load_baseline(config)
+    };
+
+    let mut diagnostics = config.severity.apply(baseline.apply(diagnostics));
+    let change_base = match (&args.since, args.staged) {
+        (Some(since), _) => Some(ChangeBase::Since(since.clone())),
+        (None, true) => Some(ChangeBase::Staged),
+        (None, false) => None,
+    };
+    if let Some(base) = change_base {
+        // The whole repository is still checked, so that keys are only unused if no file uses
+        // them, but only the problems that the changes may have caused are reported
+        let changes = Repository::discover(Path::new("."))
+            .and_then(|repository| {
+                Changes::find(&repository, &base, config, &source_filter, &locales)
+            })
+            .unwrap_or_else(|err| exit_with_error(err));
+        diagnostics = changes.filter(diagnostics);
+    }
+    let failed = print_report(&diagnostics, config, &locales, &ignore, args.format);
+
+    if args.watch {
+        watch(
+            config,
+            &source_filter,
+            locales,
+            cache,
+            ignore,
+            diagnostics,
+            args,
+        );
+    }
+    if failed {
+        std::process::exit(1);
+    }
+
+    locales
+}
+
+/// Sort the keys of the translation files, and convert them to `shape` if given.
+fn sort(locales: &mut LocaleSet, shape: Option<Shape>, text: bool) {
+    if text {
+        println!(
+            "\n{}\n",
+            style("Sorting translation files...").blue().bold()
+        );
+    }
 
-    if n_invalid_usages != 0 {
+    for locale in locales.iter_mut() {
+        locale
+            .file
+            .sort()
+            .unwrap_or_else(|err| exit_with_error(err));
+        if let Some(shape) = shape {
+            locale.file.shape = shape;
+        }
+    }
+    locales.write().unwrap_or_else(|err| exit_with_error(err));
+
+    if text {
         println!(
             "{}{}",
-            style("ERROR").red().bold(),
-            style(format!(": {} invalid key usages!", n_invalid_usages)).bold(),
+            style("SUCCESS").green().bold(),
+            style(match shape {
+                Some(_) => ": translation files sorted and converted!",
+                None => ": translation files sorted!",
+            })
+            .bold()
         );
-        std::process::exit(1);
     }
+}
+
+async fn edit(config: &Config, locales: LocaleSet) {
+    let mut lock = load_lock(config, &locales);
+    update_lock(&mut lock, &locales);
+
+    println!(
+        "\n{}\n",
+        style("Starting interactive server...").blue().bold()
+    );
+    let _ = interactive::run_server(locales, lock).await;
+}
+
+fn fix(config: &Config, args: &FixArgs) {
+    let source_filter = config
+        .source_filter()
+        .unwrap_or_else(|err| exit_with_error(err));
+    let mut locales = load_locales(config);
+    let source_files = scan::find_source_files(&config.roots, &source_filter);
+    let (key_usages, _, errors) = find_usages(config, &source_files);
+    let ignore = load_ignore_list(config);
+
+    // The keys used by files that could not be read would look unused
+    let remove_unused = errors.is_empty() || args.force;
+    let summary = fix::fix(
+        &mut locales,
+        &key_usages,
+        &ignore,
+        args.missing,
+        remove_unused,
+    );
+    let kept_unused = || {
+        if !remove_unused {
+            exit_with_error(format!(
+                "unused keys were not removed, since {} source files could not be read, run with --force to remove them anyway",
+                errors.len()
+            ));
+        }
+    };
+
+    if args.dry_run {
+        print!(
+            "{}",
+            fix::diff(&locales).unwrap_or_else(|err| exit_with_error(err))
+        );
+        println!(
+            "\n{}",
+            style(format!(
+                "Would remove {} unused keys and add {} missing keys, run without --dry-run to write the changes.",
+                summary.removed.len(),
+                summary.added.len()
+            ))
+            .bold()
+        );
+        kept_unused();
+        return;
+    }
+
+    locales.write().unwrap_or_else(|err| exit_with_error(err));
+    update_lock(&mut load_lock(config, &locales), &locales);
+    println!(
+        "{}{}",
+        style("FIXED").green().bold(),
+        style(format!(
+            ": removed {} unused keys and added {} missing keys",
+            summary.removed.len(),
+            summary.added.len()
+        ))
+        .bold()
+    );
+    kept_unused();
+}
+
+fn stats(config: &Config, json: bool) {
+    let source_filter = config
+        .source_filter()
+        .unwrap_or_else(|err| exit_with_error(err));
+    let locales = load_locales(config);
+    let source_files = scan::find_source_files(&config.roots, &source_filter);
+    let (key_usages, _, _) = find_usages(config, &source_files);
+    let ignore = load_ignore_list(config);
 
-    // Check that all keys are used
-    let ignore_unused_keys = if let Some(ignore_file) = args.ignore_file {
-        let ignore_file = std::fs::read_to_string(ignore_file).unwrap();
-        ignore_file
-            .lines()
-            .map(|line| line.trim().to_string())
-            .collect::<Vec<_>>()
+    let stats = Stats::new(
+        &locales,
+        &key_usages,
+        &ignore,
+        &config.allow_untranslated,
+        source_files.len(),
+    );
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&stats).unwrap_or_else(|err| exit_with_error(err))
+        );
     } else {
-        Vec::new()
+        print!("{}", stats.to_text());
+    }
+}
+
+fn diff(config: &Config, args: &DiffArgs) {
+    let (old, new) = match (&args.git_ref, &args.old, &args.new) {
+        (Some(git_ref), _, _) => {
+            let new = load_locales(config);
+            let repository =
+                Repository::discover(Path::new(".")).unwrap_or_else(|err| exit_with_error(err));
+            let old = diff::load_since(&repository, git_ref, &new)
+                .unwrap_or_else(|err| exit_with_error(err));
+            (old, new)
+        }
+        (None, Some(old), Some(new)) => {
+            // Two versions of a single file are compared as the same locale
+            let name = new
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let load = |path: &PathBuf| {
+                diff::load_version(path, &name).unwrap_or_else(|err| exit_with_error(err))
+            };
+            (load(old), load(new))
+        }
+        _ => unreachable!("clap requires <OLD> and <NEW>, or --ref"),
     };
 
-    let mut unused_keys = Vec::new();
-    en_translation_file
-        .unwrap()
-        .entries
-        .iter()
-        .for_each(|(key, value)| {
-            if !key_usages.iter().any(|usage| usage.key == *key)
-                && !ignore_unused_keys.contains(key)
-            {
-                println!(
-                    "{} key {}={}",
-                    style("[UNUSED]").yellow().bold(),
-                    style(key).bold(),
-                    style(format!("\"{}\"", value)).italic(),
-                );
-                unused_keys.push(key.clone());
-            }
-        });
+    print!("{}", TranslationDiff::new(&old, &new).render(args.format));
+}
 
-    if !unused_keys.is_empty() {
+/// Write the merge to `ours`, and exit with 1 if there are conflicts so that git reports them.
+fn merge_driver(base: &Path, ours: &Path, theirs: &Path) {
+    let merged = (|| -> anyhow::Result<merge::Merge> {
+        Ok(merge::merge(
+            &merge::load(base)?,
+            merge::load(ours)?,
+            &merge::load(theirs)?,
+        )?)
+    })()
+    .unwrap_or_else(|err| exit_with_error(err));
+    let content = merged
+        .serialize()
+        .unwrap_or_else(|err| exit_with_error(err));
+    std::fs::write(ours, content)
+        .unwrap_or_else(|err| exit_with_error(format!("{}: {}", ours.display(), err)));
+
+    if !merged.conflicts.is_empty() {
+        let keys = merged
+            .conflicts
+            .iter()
+            .map(|conflict| conflict.key.as_str())
+            .collect::<Vec<_>>();
         println!(
-            "{}{} {}",
-            style("ERROR").red().bold(),
-            style(format!(": {} unused keys found!", unused_keys.len(),)).bold(),
-            style(format!("({} keys ignored)", ignore_unused_keys.len())).italic()
+            "{}{}",
+            style("CONFLICT").red().bold(),
+            style(format!(
+                ": both sides changed {} keys: {}",
+                keys.len(),
+                keys.join(", ")
+            ))
+            .bold()
+        );
+        std::process::exit(1);
+    }
+}
+
+fn load_locales(config: &Config) -> LocaleSet {
+    config
+        .locale_specs()
+        .and_then(|specs| LocaleSet::load(specs, config.reference.as_deref()))
+        .unwrap_or_else(|err| exit_with_error(err))
+}
+
+/// Keys that are allowed to be unused.
+fn load_ignore_list(config: &Config) -> IgnoreList {
+    config
+        .ignore_list()
+        .unwrap_or_else(|err| exit_with_error(err))
+}
+
+/// The reference value each translation was made from.
+fn load_lock(config: &Config, locales: &LocaleSet) -> TranslationLock {
+    config
+        .translation_lock(locales)
+        .unwrap_or_else(|err| exit_with_error(err))
+}
+
+/// Record the translations that are new or changed by hand, so that only those whose reference
+/// value changed since are stale.
+fn update_lock(lock: &mut TranslationLock, locales: &LocaleSet) {
+    lock.update(locales);
+    if let Err(err) = lock.save() {
+        warn(format!("could not write lock file: {}", err));
+    }
+}
+
+/// Problems that are known, and do not fail a check.
+fn load_baseline(config: &Config) -> Baseline {
+    Baseline::load(&config.baseline_file).unwrap_or_else(|err| exit_with_error(err))
+}
+
+/// Run all checks, leaving out the problems of the baseline, and apply the configured severities.
+fn run_checks(
+    config: &Config,
+    locales: &LocaleSet,
+    key_usages: &UsageIndex,
+    ignore: &IgnoreList,
+    lock: &TranslationLock,
+    baseline: &Baseline,
+) -> Vec<Diagnostic> {
+    let diagnostics = check::run(
+        locales,
+        key_usages,
+        ignore,
+        &config.allow_untranslated,
+        lock,
+    );
+    config.severity.apply(baseline.apply(diagnostics))
+}
+
+/// Find all key usages in the source files, reusing the cache of earlier runs if enabled.
+/// Files that can not be read are skipped with a warning, and their errors returned.
+fn find_usages(
+    config: &Config,
+    source_files: &[PathBuf],
+) -> (UsageIndex, UsageCache, Vec<TSFileError>) {
+    let mut cache = match config.cache_file() {
+        Some(cache_file) => UsageCache::load(&cache_file, &config.patterns),
+        None => UsageCache::new(&config.patterns),
+    };
+    let (key_usages, errors) = scan::find_key_usages(source_files, &config.patterns, &mut cache);
+    for err in &errors {
+        warn(format!("skipping {}", err));
+    }
+    let key_usages = UsageIndex::new(key_usages);
+    save_cache(config, &cache);
+
+    (key_usages, cache, errors)
+}
+
+/// Save the key usages for the next run, if the cache is enabled.
+fn save_cache(config: &Config, cache: &UsageCache) {
+    if let Some(cache_file) = config.cache_file() {
+        if let Err(err) = cache.save(&cache_file) {
+            warn(format!("could not write cache: {}", err));
+        }
+    }
+}
+
+/// Print the diagnostics and a summary, returns whether any of them is an error.
+fn print_report(
+    diagnostics: &[Diagnostic],
+    config: &Config,
+    locales: &LocaleSet,
+    ignore: &IgnoreList,
+    format: Format,
+) -> bool {
+    let failed = report::has_errors(diagnostics);
+
+    print!("{}", report::render(diagnostics, format));
+    if format != Format::Text {
+        return failed;
+    }
+
+    if !diagnostics.is_empty() {
+        println!();
+    }
+    println!("{}", report::summary(diagnostics, &config.severity));
+
+    // How much of each locale is really translated
+    let reference = &locales.reference().file;
+    let translated = locales
+        .others()
+        .map(|locale| {
+            let untranslated = locale
+                .file
+                .find_untranslated_values(reference, &config.allow_untranslated)
+                .len();
+            let percentage = stats::translated_percentage(&locale.file, reference, untranslated);
+            format!("{} {:.1}%", locale.name, percentage)
+        })
+        .collect::<Vec<_>>();
+    if !translated.is_empty() {
+        println!(
+            "{}\n",
+            style(format!("Translated: {}", translated.join(", "))).bold()
         );
+    }
+
+    if diagnostics.iter().any(|d| d.rule == Rule::UnusedKey) {
+        let ignored_keys = locales
+            .reference()
+            .file
+            .entries
+            .keys()
+            .filter(|key| ignore.is_ignored(key))
+            .count();
         println!(
             "{}",
-            style("Unused keys should be removed from the translation files if they really are unused.").italic()
+            style(format!(
+                "Unused keys should be removed from the translation files if they really are unused, `ramilang fix` removes them ({} keys ignored).",
+                ignored_keys
+            ))
+            .italic()
         );
         println!(
             "{}",
@@ -196,44 +678,241 @@ async fn main() {
             )
             .italic()
         );
-        std::process::exit(1);
+        println!();
     }
 
-    println!(
-        "{}{}",
-        style("SUCCESS").green().bold(),
-        style(": great translations!").bold()
-    );
-
-    // Sort the translation files if requested (should maybe always be done?)
-    if args.sort {
+    if failed {
+        let errors = diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+            .count();
         println!(
-            "\n{}\n",
-            style("Sorting translation files...").blue().bold()
+            "{}{}",
+            style("ERROR").red().bold(),
+            style(format!(": {} problems found!", errors)).bold()
         );
-
-        let en_file = TranslationFile::new(args.en_file.clone()).unwrap();
-        let sv_file = TranslationFile::new(args.sv_file.clone()).unwrap();
-        // The translation files are sorted by default (BTreeMap), so we just need to write them back
-        en_file.write().expect("Unable to write EN file");
-        sv_file.write().expect("Unable to sort SV file");
-
+    } else {
         println!(
             "{}{}",
             style("SUCCESS").green().bold(),
-            style(": translation files sorted!").bold()
+            style(": great translations!").bold()
         );
     }
 
-    if args.interactive {
-        println!(
-            "\n{}\n",
-            style("Starting interactive server...").blue().bold()
-        );
-        let _ = interactive::run_server(args.en_file.as_path(), args.sv_file.as_path()).await;
+    failed
+}
+
+/// Check again whenever a translation file, the ignore file or a source file changes. Only the
+/// source files that changed are scanned again.
+fn watch(
+    config: &Config,
+    source_filter: &SourceFilter,
+    mut locales: LocaleSet,
+    mut cache: UsageCache,
+    mut ignore: IgnoreList,
+    mut previous: Vec<Diagnostic>,
+    args: &CheckArgs,
+) -> ! {
+    let format = args.format;
+    let text = format == Format::Text;
+    // Events have absolute paths, but the files are reported relative to the roots
+    let canonical = |path: &Path| std::fs::canonicalize(path).unwrap_or(path.to_path_buf());
+    let roots = config
+        .roots
+        .iter()
+        .map(|root| (root.clone(), canonical(root)))
+        .collect::<Vec<_>>();
+    let locale_files = locales
+        .iter()
+        .map(|locale| canonical(&locale.file.path))
+        .collect::<Vec<_>>();
+    let ignore_file = config.ignore_file.as_deref().map(canonical);
+    let mut lock = load_lock(config, &locales);
+    let baseline = load_baseline(config);
+
+    let mut watched_files = locales
+        .iter()
+        .map(|locale| locale.file.path.clone())
+        .collect::<Vec<_>>();
+    watched_files.extend(config.ignore_file.clone());
+    let is_excluded = |path: &Path| {
+        roots.iter().any(|(_, canonical_root)| {
+            path.strip_prefix(canonical_root)
+                .is_ok_and(|relative| source_filter.is_excluded(relative))
+        })
+    };
+
+    if text {
+        println!("\n{}", style("Watching for changes...").blue().bold());
+    }
+    let result = ramilang::watch::watch(&config.roots, &watched_files, is_excluded, |changed| {
+        let reload_locales = changed.iter().any(|path| locale_files.contains(path));
+        let reload_ignore = changed
+            .iter()
+            .any(|path| Some(path) == ignore_file.as_ref());
+        let sources = changed
+            .iter()
+            .filter_map(|path| {
+                roots.iter().find_map(|(root, canonical_root)| {
+                    let relative = path.strip_prefix(canonical_root).ok()?;
+                    source_filter
+                        .contains(relative)
+                        .then(|| root.join(relative))
+                })
+            })
+            .collect::<Vec<_>>();
+        if !reload_locales && !reload_ignore && sources.is_empty() {
+            return;
+        }
+
+        if text {
+            println!(
+                "\n{}\n",
+                style(format!(
+                    "{} files changed, checking translations...",
+                    changed.len()
+                ))
+                .blue()
+                .bold()
+            );
+        }
+
+        // Keep the previous state of anything that can not be loaded, until it is fixed
+        let reloaded = (|| -> anyhow::Result<()> {
+            if reload_locales {
+                locales = config
+                    .locale_specs()
+                    .and_then(|specs| LocaleSet::load(specs, config.reference.as_deref()))?;
+                if args.update_lock {
+                    update_lock(&mut lock, &locales);
+                }
+            }
+            if reload_ignore {
+                ignore = config.ignore_list()?;
+            }
+            for err in cache.rescan(&sources, &config.patterns) {
+                warn(format!("skipping {}", err));
+            }
+            save_cache(config, &cache);
+            Ok(())
+        })();
+        if let Err(err) = reloaded {
+            println!(
+                "{}{}",
+                style("ERROR").red().bold(),
+                style(format!(": {}", err)).bold()
+            );
+            return;
+        }
+
+        let key_usages = UsageIndex::new(cache.usages());
+        let diagnostics = run_checks(config, &locales, &key_usages, &ignore, &lock, &baseline);
+        print_report(&diagnostics, config, &locales, &ignore, format);
+
+        if text {
+            let new = diagnostics.iter().filter(|d| !previous.contains(d)).count();
+            let fixed = previous.iter().filter(|d| !diagnostics.contains(d)).count();
+            println!(
+                "{}",
+                style(format!(
+                    "{} new and {} fixed problems since the last check",
+                    new, fixed
+                ))
+                .italic()
+            );
+        }
+        previous = diagnostics;
+    });
+
+    match result {
+        Ok(()) => std::process::exit(0),
+        Err(err) => exit_with_error(err),
+    }
+}
+
+/// Load the config file, with the command line arguments taking precedence over it.
+fn load_config(args: &Options) -> anyhow::Result<Config> {
+    let mut config = match &args.config {
+        Some(path) => Config::load(path)?,
+        None => Config::discover(Path::new("."))?
+            .map(|(_, config)| config)
+            .unwrap_or_default(),
+    };
+
+    let mut locales = Vec::new();
+    if let Some(en_file) = &args.en_file {
+        locales.push(LocaleSpec {
+            name: "en".to_string(),
+            path: en_file.clone(),
+        });
+    }
+    if let Some(sv_file) = &args.sv_file {
+        locales.push(LocaleSpec {
+            name: "sv".to_string(),
+            path: sv_file.clone(),
+        });
+    }
+    locales.extend(args.locales.iter().cloned());
+    if !locales.is_empty() || args.locales_dir.is_some() {
+        config.locales.clear();
+        for spec in locales {
+            if config
+                .locales
+                .insert(spec.name.clone(), spec.path)
+                .is_some()
+            {
+                return Err(LocaleError::DuplicateLocale(spec.name).into());
+            }
+        }
+        config.locales_dir = args.locales_dir.clone();
+    }
+
+    if let Some(reference) = &args.reference {
+        config.reference = Some(reference.clone());
+    }
+    if let Some(root_dir) = &args.root_dir {
+        config.roots = vec![root_dir.clone()];
+    }
+    if !args.extensions.is_empty() {
+        config.extensions = args.extensions.clone();
     }
+    if !args.include.is_empty() {
+        config.include = args.include.clone();
+    }
+    if !args.exclude.is_empty() {
+        config.exclude = args.exclude.clone();
+    }
+    if let Some(ignore_file) = &args.ignore_file {
+        config.ignore_file = Some(ignore_file.clone());
+    }
+    if args.no_cache {
+        config.cache = false;
+    }
+
+    Ok(config)
+}
+
+fn parse_severity(s: &str) -> Result<(Rule, Level), String> {
+    let (rule, level) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected <RULE>=<LEVEL>, got \"{}\"", s))?;
+    Ok((rule.parse()?, level.parse()?))
 }
 
-fn is_node_modules(entry: &DirEntry) -> bool {
-    entry.file_name() == "node_modules"
+/// Print a warning to stderr.
+fn warn(message: impl Display) {
+    eprintln!(
+        "{}{}",
+        style("WARNING").yellow().bold(),
+        style(format!(": {}", message)).bold()
+    );
+}
+
+fn exit_with_error(err: impl Display) -> ! {
+    println!(
+        "{}{}",
+        style("ERROR").red().bold(),
+        style(format!(": {}", err)).bold()
+    );
+    std::process::exit(1);
 }
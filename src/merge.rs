@@ -0,0 +1,220 @@
+use std::{collections::BTreeSet, path::Path};
+
+use anyhow::Result;
+use similar::{ChangeTag, TextDiff};
+
+use crate::translation_file::{TranslationFile, TranslationFileError};
+
+/// The result of a three-way merge of a translation file, key by key.
+#[derive(Debug, Clone)]
+pub struct Merge {
+    /// Our version of the file with the changes of both sides, sorted. Conflicting keys have our
+    /// value.
+    pub file: TranslationFile,
+    /// Keys that both sides changed to a different value, sorted by key
+    pub conflicts: Vec<Conflict>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub key: String,
+    /// `None` if the side removed the key
+    pub ours: Option<String>,
+    pub theirs: Option<String>,
+}
+
+/// Load one version of a merged file. Git passes an empty file for the base of a file that was
+/// added on both sides.
+pub fn load(path: &Path) -> Result<TranslationFile, TranslationFileError> {
+    let content = std::fs::read_to_string(path).map_err(|source| TranslationFileError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let content = if content.trim().is_empty() {
+        "{}"
+    } else {
+        &content
+    };
+    TranslationFile::parse(path.to_path_buf(), content)
+}
+
+/// Merge the changes from `base` to `theirs` into `ours`. A key that only one side changed,
+/// added or removed gets the value of that side, and a key that both sides changed the same way
+/// gets that value. The file is sorted, as `ramilang sort` would.
+pub fn merge(
+    base: &TranslationFile,
+    mut ours: TranslationFile,
+    theirs: &TranslationFile,
+) -> Result<Merge, TranslationFileError> {
+    let keys = base
+        .entries
+        .keys()
+        .chain(ours.entries.keys())
+        .chain(theirs.entries.keys())
+        .cloned()
+        .collect::<BTreeSet<_>>();
+
+    let mut conflicts = Vec::new();
+    for key in keys {
+        let base_value = base.entries.get(&key);
+        let our_value = ours.entries.get(&key);
+        let their_value = theirs.entries.get(&key);
+        if our_value == their_value || their_value == base_value {
+            continue;
+        }
+        if our_value != base_value {
+            conflicts.push(Conflict {
+                key,
+                ours: our_value.cloned(),
+                theirs: their_value.cloned(),
+            });
+            continue;
+        }
+        match their_value {
+            Some(value) => ours.entries.insert(key, value.clone()),
+            None => ours.entries.remove(&key),
+        };
+    }
+
+    ours.sort()?;
+    Ok(Merge {
+        file: ours,
+        conflicts,
+    })
+}
+
+impl Merge {
+    /// The merged file, with git conflict markers around the lines where the conflicting keys
+    /// make the two sides differ. Picking either side of every conflict gives a valid file.
+    pub fn serialize(&self) -> Result<String> {
+        if self.conflicts.is_empty() {
+            return self.file.serialize();
+        }
+
+        let ours = self.side(|conflict| &conflict.ours)?.serialize()?;
+        let theirs = self.side(|conflict| &conflict.theirs)?.serialize()?;
+        let line_ending = if ours.contains("\r\n") { "\r\n" } else { "\n" };
+
+        // Lines that differ between the sides, including the trailing comma of a line before a
+        // key that only one side has
+        let mut merged = String::new();
+        let mut hunk: Option<(String, String)> = None;
+        let end_hunk = |merged: &mut String, hunk: &mut Option<(String, String)>| {
+            let Some((ours, theirs)) = hunk.take() else {
+                return;
+            };
+            for (marker, side) in [("<<<<<<< ours", ours), ("=======", theirs)] {
+                merged.push_str(marker);
+                merged.push_str(line_ending);
+                merged.push_str(&side);
+                if !side.is_empty() && !side.ends_with('\n') {
+                    merged.push_str(line_ending);
+                }
+            }
+            merged.push_str(">>>>>>> theirs");
+            merged.push_str(line_ending);
+        };
+        for change in TextDiff::from_lines(&ours, &theirs).iter_all_changes() {
+            match change.tag() {
+                ChangeTag::Equal => {
+                    end_hunk(&mut merged, &mut hunk);
+                    merged.push_str(change.value());
+                }
+                ChangeTag::Delete => hunk.get_or_insert_default().0.push_str(change.value()),
+                ChangeTag::Insert => hunk.get_or_insert_default().1.push_str(change.value()),
+            }
+        }
+        end_hunk(&mut merged, &mut hunk);
+        Ok(merged)
+    }
+
+    /// The merged file with the value of one side for every conflicting key.
+    fn side(&self, value: fn(&Conflict) -> &Option<String>) -> Result<TranslationFile> {
+        let mut file = self.file.clone();
+        for conflict in &self.conflicts {
+            match value(conflict) {
+                Some(value) => file.entries.insert(conflict.key.clone(), value.clone()),
+                None => file.entries.remove(&conflict.key),
+            };
+        }
+        // Sorting again puts keys that we removed in their place
+        file.sort()?;
+        Ok(file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn file(content: &str) -> TranslationFile {
+        TranslationFile::parse(PathBuf::from("sv.json"), content).unwrap()
+    }
+
+    #[test]
+    fn test_merge() {
+        let base = file(r#"{ "a": "A", "b": "B", "c": "C", "d": "D" }"#);
+        // Ours adds "e", removes "b" and changes "c" and "d"
+        let ours = file(r#"{ "a": "A", "c": "C2", "d": "D2", "e": "E" }"#);
+        // Theirs adds "f", changes "a" and "d" and also changes "c" to "C2"
+        let theirs = file(r#"{ "a": "A2", "b": "B", "c": "C2", "d": "D3", "f": "F" }"#);
+
+        let merge = merge(&base, ours, &theirs).unwrap();
+
+        assert_eq!(
+            vec![
+                ("a", "A2"),
+                ("c", "C2"),
+                ("d", "D2"),
+                ("e", "E"),
+                ("f", "F")
+            ],
+            merge
+                .file
+                .entries
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_str()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![Conflict {
+                key: "d".to_string(),
+                ours: Some("D2".to_string()),
+                theirs: Some("D3".to_string()),
+            }],
+            merge.conflicts
+        );
+    }
+
+    #[test]
+    fn test_serialize_conflicts() {
+        let base = file("{\n  \"b\": \"B\",\n  \"a\": \"A\",\n  \"c\": \"C\"\n}\n");
+        let ours = file("{\n  \"b\": \"B2\",\n  \"a\": \"A\"\n}\n");
+        let theirs = file("{\n  \"b\": \"B3\",\n  \"a\": \"A\",\n  \"c\": \"C2\"\n}\n");
+
+        let merged = merge(&base, ours, &theirs).unwrap().serialize().unwrap();
+
+        assert_eq!(
+            "{\n  \"a\": \"A\",\n<<<<<<< ours\n  \"b\": \"B2\"\n=======\n  \"b\": \"B3\",\n  \"c\": \"C2\"\n>>>>>>> theirs\n}\n",
+            merged
+        );
+
+        // Either side of the conflict is valid JSON
+        for (side, keys) in [("ours", vec!["a", "b"]), ("theirs", vec!["a", "b", "c"])] {
+            let mut resolved = String::new();
+            let mut keep = true;
+            for line in merged.lines() {
+                match line {
+                    "<<<<<<< ours" => keep = side == "ours",
+                    "=======" => keep = side == "theirs",
+                    ">>>>>>> theirs" => keep = true,
+                    line if keep => resolved.push_str(line),
+                    _ => {}
+                }
+            }
+            assert_eq!(keys, file(&resolved).entries.keys().collect::<Vec<_>>());
+        }
+    }
+}
@@ -0,0 +1,778 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use clap::ValueEnum;
+use console::style;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    baseline::BaselineEntry, ignore::IgnoreEntry, translation_file::TranslationFileError,
+    ts_file::KeyUsage,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    DuplicateKey,
+    MissingKey,
+    EmptyValue,
+    InvalidMessage,
+    PlaceholderMismatch,
+    Untranslated,
+    StaleTranslation,
+    InvalidKey,
+    MissingValue,
+    UnusedValue,
+    UnusedKey,
+    StaleIgnore,
+    StaleBaseline,
+}
+
+/// How seriously to take the problems found by a rule. Only errors make a check fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Off,
+    Warning,
+    Error,
+}
+
+/// The level of each rule, as configured in the `[severity]` table of the config file.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct Severities(BTreeMap<Rule, Level>);
+
+/// A single problem found by one of the checks.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub rule: Rule,
+    pub severity: Severity,
+    pub message: String,
+    pub key: Option<String>,
+    pub file: Option<PathBuf>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Colored text for humans
+    Text,
+    /// JSON with all diagnostics
+    Json,
+    /// SARIF 2.1.0, for code scanning
+    Sarif,
+    /// JUnit XML, one test suite per rule
+    Junit,
+    /// GitHub Actions workflow commands (`::error file=...::`)
+    Github,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 13] = [
+        Rule::DuplicateKey,
+        Rule::MissingKey,
+        Rule::EmptyValue,
+        Rule::InvalidMessage,
+        Rule::PlaceholderMismatch,
+        Rule::Untranslated,
+        Rule::StaleTranslation,
+        Rule::InvalidKey,
+        Rule::MissingValue,
+        Rule::UnusedValue,
+        Rule::UnusedKey,
+        Rule::StaleIgnore,
+        Rule::StaleBaseline,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Rule::DuplicateKey => "duplicate-key",
+            Rule::MissingKey => "missing-key",
+            Rule::EmptyValue => "empty-value",
+            Rule::InvalidMessage => "invalid-message",
+            Rule::PlaceholderMismatch => "placeholder-mismatch",
+            Rule::Untranslated => "untranslated",
+            Rule::StaleTranslation => "stale-translation",
+            Rule::InvalidKey => "invalid-key",
+            Rule::MissingValue => "missing-value",
+            Rule::UnusedValue => "unused-value",
+            Rule::UnusedKey => "unused-key",
+            Rule::StaleIgnore => "stale-ignore",
+            Rule::StaleBaseline => "stale-baseline",
+        }
+    }
+
+    /// Short label used in the text output, e.g. `[MISSING]`.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Rule::DuplicateKey => "DUPLICATE",
+            Rule::MissingKey => "MISSING",
+            Rule::EmptyValue => "EMPTY",
+            Rule::InvalidMessage => "SYNTAX",
+            Rule::PlaceholderMismatch => "PLACEHOLDER",
+            Rule::Untranslated => "UNTRANSLATED",
+            Rule::StaleTranslation => "OUTDATED",
+            Rule::InvalidKey => "INVALID",
+            Rule::MissingValue => "VALUE",
+            Rule::UnusedValue => "EXTRA VALUE",
+            Rule::UnusedKey => "UNUSED",
+            Rule::StaleIgnore => "STALE",
+            Rule::StaleBaseline => "FIXED",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Rule::DuplicateKey => "A key is defined more than once in a translation file",
+            Rule::MissingKey => "A key is missing from one of the translation files",
+            Rule::EmptyValue => "A key has an empty value",
+            Rule::InvalidMessage => "A value is not a valid ICU MessageFormat message",
+            Rule::PlaceholderMismatch => {
+                "A value uses other arguments or tags than the value of the reference locale"
+            }
+            Rule::Untranslated => "A value is the same as the value of the reference locale",
+            Rule::StaleTranslation => {
+                "The value of the reference locale changed since a value was translated"
+            }
+            Rule::InvalidKey => "The code uses a key that does not exist",
+            Rule::MissingValue => "The code does not pass a value for an argument of the message",
+            Rule::UnusedValue => "The code passes a value that the message does not use",
+            Rule::UnusedKey => "A key is not used anywhere in the code",
+            Rule::StaleIgnore => "An entry of the ignore file does not match any key",
+            Rule::StaleBaseline => "A problem of the baseline is not found anymore",
+        }
+    }
+
+    /// Level of the rule when it is not configured.
+    pub fn default_level(&self) -> Level {
+        match self {
+            Rule::Untranslated | Rule::StaleTranslation | Rule::UnusedValue | Rule::StaleIgnore => {
+                Level::Warning
+            }
+            _ => Level::Error,
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Rule::ALL
+            .into_iter()
+            .find(|rule| rule.id() == s)
+            .ok_or_else(|| {
+                let ids = Rule::ALL.map(|rule| rule.id());
+                format!("unknown rule \"{}\", expected one of {}", s, ids.join(", "))
+            })
+    }
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Level::Off),
+            "warning" | "warn" => Ok(Level::Warning),
+            "error" => Ok(Level::Error),
+            _ => Err(format!(
+                "unknown severity \"{}\", expected off, warning or error",
+                s
+            )),
+        }
+    }
+}
+
+impl Severities {
+    pub fn set(&mut self, rule: Rule, level: Level) {
+        self.0.insert(rule, level);
+    }
+
+    pub fn level(&self, rule: Rule) -> Level {
+        self.0
+            .get(&rule)
+            .copied()
+            .unwrap_or_else(|| rule.default_level())
+    }
+
+    /// Give each diagnostic the severity of its rule, dropping those of disabled rules.
+    pub fn apply(&self, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        diagnostics
+            .into_iter()
+            .filter_map(|diagnostic| {
+                let severity = match self.level(diagnostic.rule) {
+                    Level::Off => return None,
+                    Level::Warning => Severity::Warning,
+                    Level::Error => Severity::Error,
+                };
+                Some(Diagnostic {
+                    severity,
+                    ..diagnostic
+                })
+            })
+            .collect()
+    }
+}
+
+impl Severity {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+impl Diagnostic {
+    /// A usage of a key that does not exist, or a pattern that does not match any key.
+    pub fn invalid_key(usage: &KeyUsage) -> Self {
+        let message = if usage.dynamic {
+            format!("key pattern \"{}\" does not match any key", usage.key)
+        } else {
+            format!("key \"{}\" does not exist", usage.key)
+        };
+
+        Self {
+            rule: Rule::InvalidKey,
+            severity: Severity::Error,
+            message,
+            key: Some(usage.key.clone()),
+            file: Some(usage.file_path.clone()),
+            line: Some(usage.line),
+            column: Some(usage.column),
+        }
+    }
+
+    /// A usage that does not pass a value for `argument`, which the message of the key needs.
+    pub fn missing_value(usage: &KeyUsage, argument: &str) -> Self {
+        Self {
+            rule: Rule::MissingValue,
+            message: format!(
+                "key \"{}\" needs a value for {{{}}}, which is not passed",
+                usage.key, argument
+            ),
+            ..Self::invalid_key(usage)
+        }
+    }
+
+    /// A usage that passes the value `name`, which the message of the key does not use.
+    pub fn unused_value(usage: &KeyUsage, name: &str) -> Self {
+        Self {
+            rule: Rule::UnusedValue,
+            message: format!(
+                "value \"{}\" is passed to key \"{}\", which does not use it",
+                name, usage.key
+            ),
+            ..Self::invalid_key(usage)
+        }
+    }
+
+    pub fn unused_key(key: &str, value: &str, path: PathBuf) -> Self {
+        Self {
+            rule: Rule::UnusedKey,
+            severity: Severity::Error,
+            message: format!("key \"{}\" (\"{}\") is not used", key, value),
+            key: Some(key.to_string()),
+            file: Some(path),
+            line: None,
+            column: None,
+        }
+    }
+
+    /// An entry of the ignore file that does not match any key.
+    pub fn stale_ignore(entry: &IgnoreEntry) -> Self {
+        let pattern = if entry.negated {
+            format!("!{}", entry.pattern)
+        } else {
+            entry.pattern.clone()
+        };
+
+        Self {
+            rule: Rule::StaleIgnore,
+            severity: Severity::Error,
+            message: format!("ignore entry \"{}\" does not match any key", pattern),
+            key: Some(pattern),
+            file: entry.file.clone(),
+            line: entry.line,
+            column: None,
+        }
+    }
+
+    /// A problem of the baseline at `path` that was `fixed` times not found.
+    pub fn stale_baseline(entry: &BaselineEntry, fixed: usize, path: &Path) -> Self {
+        let mut problem = entry.rule.id().to_string();
+        if let Some(key) = &entry.key {
+            let _ = write!(problem, " of key \"{}\"", key);
+        }
+        if let Some(file) = &entry.file {
+            let _ = write!(problem, " in {}", file);
+        }
+        let message = if fixed == entry.count {
+            format!("{} is fixed, update the baseline to remove it", problem)
+        } else {
+            format!(
+                "{} is fixed {} of {} times, update the baseline to remove them",
+                problem, fixed, entry.count
+            )
+        };
+
+        Self {
+            rule: Rule::StaleBaseline,
+            severity: Severity::Error,
+            message,
+            key: entry.key.clone(),
+            file: Some(path.to_path_buf()),
+            line: None,
+            column: None,
+        }
+    }
+
+    /// `file:line:column`, as far as it is known.
+    pub fn location(&self) -> Option<String> {
+        let file = self.file.as_ref()?;
+        Some(match (self.line, self.column) {
+            (Some(line), Some(column)) => format!("{}:{}:{}", file.display(), line, column),
+            (Some(line), None) => format!("{}:{}", file.display(), line),
+            _ => file.display().to_string(),
+        })
+    }
+
+    /// One colored line for the text output.
+    pub fn to_text(&self) -> String {
+        let label = format!("[{}]", self.rule.label());
+        let label = match self.severity {
+            Severity::Error => style(label).yellow().bold(),
+            Severity::Warning => style(label).cyan().bold(),
+        };
+        match self.location() {
+            Some(location) => format!(
+                "{} {} {}",
+                label,
+                style(&self.message).bold(),
+                style(format!("({})", location)).italic().dim()
+            ),
+            None => format!("{} {}", label, style(&self.message).bold()),
+        }
+    }
+}
+
+/// Turn the errors of a translation file check into diagnostics, one per key.
+pub fn translation_file_diagnostics(error: &TranslationFileError) -> Vec<Diagnostic> {
+    let diagnostic = |rule, message: String, key: &str, file: &PathBuf| Diagnostic {
+        rule,
+        severity: Severity::Error,
+        message,
+        key: Some(key.to_string()),
+        file: Some(file.clone()),
+        line: None,
+        column: None,
+    };
+
+    match error {
+        TranslationFileError::MissingKey { key, missing_in } => {
+            vec![diagnostic(Rule::MissingKey, error.to_string(), key, missing_in)]
+        }
+        TranslationFileError::EmptyValue { key, path } => {
+            vec![diagnostic(Rule::EmptyValue, error.to_string(), key, path)]
+        }
+        TranslationFileError::InvalidMessage { key, path, .. } => {
+            vec![diagnostic(Rule::InvalidMessage, error.to_string(), key, path)]
+        }
+        TranslationFileError::MissingArgument { key, path, .. }
+        | TranslationFileError::UnknownArgument { key, path, .. }
+        | TranslationFileError::ArgumentTypeMismatch { key, path, .. }
+        | TranslationFileError::MissingTag { key, path, .. }
+        | TranslationFileError::UnknownTag { key, path, .. } => vec![diagnostic(
+            Rule::PlaceholderMismatch,
+            error.to_string(),
+            key,
+            path,
+        )],
+        TranslationFileError::UntranslatedValue { key, path, .. } => {
+            vec![diagnostic(Rule::Untranslated, error.to_string(), key, path)]
+        }
+        TranslationFileError::StaleTranslation { key, path, .. } => vec![diagnostic(
+            Rule::StaleTranslation,
+            error.to_string(),
+            key,
+            path,
+        )],
+        // One diagnostic for every definition after the first
+        TranslationFileError::DuplicateKeys(path, duplicates) => duplicates
+            .iter()
+            .flat_map(|duplicate| {
+                let first = &duplicate.occurrences[0];
+                duplicate.occurrences[1..].iter().map(move |occurrence| {
+                    let message = if occurrence.value == first.value {
+                        format!(
+                            "key \"{}\" is already defined at line {}, with the same value",
+                            duplicate.key, first.line
+                        )
+                    } else {
+                        format!(
+                            "key \"{}\" is already defined at line {}, with a conflicting value ({} and {})",
+                            duplicate.key, first.line, first.value, occurrence.value
+                        )
+                    };
+                    Diagnostic {
+                        line: Some(occurrence.line),
+                        column: Some(occurrence.column),
+                        ..diagnostic(Rule::DuplicateKey, message, &duplicate.key, path)
+                    }
+                })
+            })
+            .collect(),
+        // Reported when reading or writing the file, not found by any of the checks
+        TranslationFileError::Io { .. }
+        | TranslationFileError::Parse { .. }
+        | TranslationFileError::InvalidValue { .. }
+        | TranslationFileError::NestingConflict { .. } => Vec::new(),
+    }
+}
+
+/// Render the diagnostics in one of the machine readable formats, or as text.
+pub fn render(diagnostics: &[Diagnostic], format: Format) -> String {
+    match format {
+        Format::Text => diagnostics
+            .iter()
+            .map(|diagnostic| format!("{}\n", diagnostic.to_text()))
+            .collect(),
+        Format::Json => render_json(diagnostics),
+        Format::Sarif => render_sarif(diagnostics),
+        Format::Junit => render_junit(diagnostics),
+        Format::Github => render_github(diagnostics),
+    }
+}
+
+/// Number of errors and warnings per rule, for the text output.
+pub fn summary(diagnostics: &[Diagnostic], severities: &Severities) -> String {
+    let mut summary = format!("{}\n", style("Summary").bold().underlined());
+    for rule in Rule::ALL {
+        let in_rule = diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.rule == rule)
+            .collect::<Vec<_>>();
+        let errors = in_rule
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .count();
+        let warnings = in_rule.len() - errors;
+
+        let result = match (severities.level(rule), errors, warnings) {
+            (Level::Off, _, _) => style("off".to_string()).dim(),
+            (_, 0, 0) => style("ok".to_string()).green(),
+            (_, 0, warnings) => style(format!("{} warnings", warnings)).cyan(),
+            (_, errors, 0) => style(format!("{} errors", errors)).red(),
+            (_, errors, warnings) => {
+                style(format!("{} errors, {} warnings", errors, warnings)).red()
+            }
+        };
+        let _ = writeln!(summary, "  {:<20} {}", rule.id(), result);
+    }
+    summary
+}
+
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+}
+
+fn count(diagnostics: &[Diagnostic], severity: Severity) -> usize {
+    diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == severity)
+        .count()
+}
+
+/// Paths in reports use forward slashes and no leading `./`.
+fn report_path(path: &std::path::Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    path.strip_prefix("./").unwrap_or(&path).to_string()
+}
+
+fn render_json(diagnostics: &[Diagnostic]) -> String {
+    let rules = Rule::ALL
+        .iter()
+        .map(|rule| {
+            let in_rule = diagnostics
+                .iter()
+                .filter(|diagnostic| diagnostic.rule == *rule)
+                .cloned()
+                .collect::<Vec<_>>();
+            (
+                rule.id().to_string(),
+                json!({
+                    "errors": count(&in_rule, Severity::Error),
+                    "warnings": count(&in_rule, Severity::Warning),
+                }),
+            )
+        })
+        .collect::<serde_json::Map<_, _>>();
+
+    let report = json!({
+        "diagnostics": diagnostics,
+        "summary": {
+            "errors": count(diagnostics, Severity::Error),
+            "warnings": count(diagnostics, Severity::Warning),
+            "rules": rules,
+        },
+    });
+    serde_json::to_string_pretty(&report).unwrap_or_default() + "\n"
+}
+
+fn render_sarif(diagnostics: &[Diagnostic]) -> String {
+    let mut rules = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.rule)
+        .collect::<Vec<_>>();
+    rules.sort();
+    rules.dedup();
+
+    let results = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let mut result = json!({
+                "ruleId": diagnostic.rule.id(),
+                "level": diagnostic.severity.as_str(),
+                "message": { "text": diagnostic.message },
+            });
+            if let Some(file) = &diagnostic.file {
+                let mut location = json!({
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": report_path(file) },
+                    },
+                });
+                if let Some(line) = diagnostic.line {
+                    location["physicalLocation"]["region"] = json!({
+                        "startLine": line,
+                        "startColumn": diagnostic.column.unwrap_or(1),
+                    });
+                }
+                result["locations"] = json!([location]);
+            }
+            result
+        })
+        .collect::<Vec<_>>();
+
+    let report = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "ramilang",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules.iter().map(|rule| json!({
+                        "id": rule.id(),
+                        "shortDescription": { "text": rule.description() },
+                    })).collect::<Vec<_>>(),
+                },
+            },
+            "results": results,
+        }],
+    });
+    serde_json::to_string_pretty(&report).unwrap_or_default() + "\n"
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn render_junit(diagnostics: &[Diagnostic]) -> String {
+    let errors = count(diagnostics, Severity::Error);
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"ramilang\" tests=\"{}\" failures=\"{}\">",
+        diagnostics.len().max(1),
+        errors
+    );
+
+    if diagnostics.is_empty() {
+        xml.push_str("  <testsuite name=\"ramilang\" tests=\"1\" failures=\"0\">\n");
+        xml.push_str("    <testcase name=\"translations\" classname=\"ramilang\" />\n");
+        xml.push_str("  </testsuite>\n");
+    }
+
+    let mut rules = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.rule)
+        .collect::<Vec<_>>();
+    rules.sort();
+    rules.dedup();
+
+    for rule in rules {
+        let in_rule = diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.rule == rule)
+            .collect::<Vec<_>>();
+        let failures = in_rule
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .count();
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">",
+            rule.id(),
+            in_rule.len(),
+            failures
+        );
+
+        for diagnostic in in_rule {
+            let name = diagnostic.key.as_deref().unwrap_or(&diagnostic.message);
+            let mut attributes = format!(
+                "name=\"{}\" classname=\"ramilang.{}\"",
+                escape_xml(name),
+                rule.id()
+            );
+            if let Some(file) = &diagnostic.file {
+                let _ = write!(attributes, " file=\"{}\"", escape_xml(&report_path(file)));
+            }
+            if let Some(line) = diagnostic.line {
+                let _ = write!(attributes, " line=\"{}\"", line);
+            }
+
+            let _ = writeln!(xml, "    <testcase {}>", attributes);
+            let details = escape_xml(&diagnostic.location().unwrap_or_default());
+            match diagnostic.severity {
+                Severity::Error => {
+                    let _ = writeln!(
+                        xml,
+                        "      <failure message=\"{}\" type=\"{}\">{}</failure>",
+                        escape_xml(&diagnostic.message),
+                        rule.id(),
+                        details
+                    );
+                }
+                Severity::Warning => {
+                    let _ = writeln!(
+                        xml,
+                        "      <system-out>warning: {} {}</system-out>",
+                        escape_xml(&diagnostic.message),
+                        details
+                    );
+                }
+            }
+            xml.push_str("    </testcase>\n");
+        }
+
+        xml.push_str("  </testsuite>\n");
+    }
+
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn escape_github_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_github_property(s: &str) -> String {
+    escape_github_data(s)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+fn render_github(diagnostics: &[Diagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let mut properties = Vec::new();
+            if let Some(file) = &diagnostic.file {
+                properties.push(format!(
+                    "file={}",
+                    escape_github_property(&report_path(file))
+                ));
+            }
+            if let Some(line) = diagnostic.line {
+                properties.push(format!("line={}", line));
+            }
+            if let Some(column) = diagnostic.column {
+                properties.push(format!("col={}", column));
+            }
+            properties.push(format!("title={}", diagnostic.rule.id()));
+
+            format!(
+                "::{} {}::{}\n",
+                diagnostic.severity.as_str(),
+                properties.join(","),
+                escape_github_data(&diagnostic.message)
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostics() -> Vec<Diagnostic> {
+        vec![
+            Diagnostic::invalid_key(&KeyUsage {
+                line: 12,
+                column: 5,
+                ..KeyUsage::test("common.sav", "./src/Button.tsx")
+            }),
+            Diagnostic {
+                severity: Severity::Warning,
+                ..Diagnostic::unused_key("old", "Old, & <b>bold</b>", PathBuf::from("en.json"))
+            },
+        ]
+    }
+
+    #[test]
+    fn test_render_github() {
+        let expected = "::error file=src/Button.tsx,line=12,col=5,title=invalid-key::key \"common.sav\" does not exist\n\
+                        ::warning file=en.json,title=unused-key::key \"old\" (\"Old, & <b>bold</b>\") is not used\n";
+        assert_eq!(expected, render(&diagnostics(), Format::Github));
+    }
+
+    #[test]
+    fn test_render_sarif() {
+        let sarif: serde_json::Value =
+            serde_json::from_str(&render(&diagnostics(), Format::Sarif)).unwrap();
+        let results = &sarif["runs"][0]["results"];
+
+        assert_eq!("2.1.0", sarif["version"]);
+        assert_eq!("invalid-key", results[0]["ruleId"]);
+        assert_eq!(
+            "src/Button.tsx",
+            results[0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"]
+        );
+        assert_eq!(
+            12,
+            results[0]["locations"][0]["physicalLocation"]["region"]["startLine"]
+        );
+        assert_eq!("warning", results[1]["level"]);
+    }
+
+    #[test]
+    fn test_render_junit() {
+        let xml = render(&diagnostics(), Format::Junit);
+
+        assert!(xml.contains("<testsuites name=\"ramilang\" tests=\"2\" failures=\"1\">"));
+        assert!(xml.contains("<testcase name=\"common.sav\" classname=\"ramilang.invalid-key\" file=\"src/Button.tsx\" line=\"12\">"));
+        assert!(xml.contains("(&quot;Old, &amp; &lt;b&gt;bold&lt;/b&gt;&quot;)"));
+    }
+}
@@ -0,0 +1,250 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+use crate::{
+    config::SourceFilter,
+    ts_file::{KeyUsage, TSFile, TSFileError, UsagePatterns},
+};
+
+/// Key usages of the source files from earlier runs, so that unchanged files are not parsed
+/// again.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UsageCache {
+    /// Version of ramilang that wrote the cache, the extraction may change between versions
+    version: String,
+    /// Patterns the usages were found with
+    patterns: UsagePatterns,
+    files: HashMap<PathBuf, CachedFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedFile {
+    /// Modification time in nanoseconds since the epoch
+    modified: u128,
+    /// SHA-256 of the content, for files that were touched without being changed
+    hash: String,
+    usages: Vec<KeyUsage>,
+}
+
+impl UsageCache {
+    pub fn new(patterns: &UsagePatterns) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            patterns: patterns.clone(),
+            files: HashMap::new(),
+        }
+    }
+
+    /// Load the cache from `path`. A cache that is missing, unreadable or was written by another
+    /// version or with other patterns is treated as empty.
+    pub fn load(path: &Path, patterns: &UsagePatterns) -> Self {
+        let cache = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<Self>(&content).ok())
+            .filter(|cache| {
+                cache.version == env!("CARGO_PKG_VERSION") && cache.patterns == *patterns
+            });
+
+        cache.unwrap_or_else(|| Self::new(patterns))
+    }
+
+    /// Scan the given files again, and forget those that no longer exist. Used to update the
+    /// usages when only a few files changed. Files that can not be read are forgotten too, and
+    /// their errors returned.
+    pub fn rescan(&mut self, files: &[PathBuf], patterns: &UsagePatterns) -> Vec<TSFileError> {
+        let (existing, removed): (Vec<_>, Vec<_>) = files.iter().partition(|path| path.is_file());
+        for path in removed {
+            self.files.remove(path);
+        }
+
+        let scanned = existing
+            .par_iter()
+            .map(|path| scan_file(path, patterns, self.files.get(*path)))
+            .collect::<Vec<_>>();
+
+        let mut errors = Vec::new();
+        for (path, file) in existing.into_iter().zip(scanned) {
+            match file {
+                Ok(file) => {
+                    self.files.insert(path.clone(), file);
+                }
+                Err(err) => {
+                    self.files.remove(path);
+                    errors.push(err);
+                }
+            }
+        }
+        errors
+    }
+
+    /// The usages of all cached files, ordered by file.
+    pub fn usages(&self) -> Vec<KeyUsage> {
+        let mut paths = self.files.keys().collect::<Vec<_>>();
+        paths.sort();
+        paths
+            .into_iter()
+            .flat_map(|path| self.files[path].usages.iter().cloned())
+            .collect()
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)
+    }
+}
+
+/// Every source file in the roots, in a stable order.
+pub fn find_source_files(roots: &[PathBuf], filter: &SourceFilter) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for root in roots {
+        let relative = |path: &Path| path.strip_prefix(root).unwrap_or(path).to_path_buf();
+        let walker = WalkDir::new(root)
+            .sort_by_file_name()
+            .into_iter()
+            // Exclude node_modules and anything else excluded in the config
+            .filter_entry(|e| e.depth() == 0 || !filter.is_excluded(&relative(e.path())))
+            // Filter out any non-accessible files
+            .filter_map(|e| e.ok());
+
+        files.extend(
+            walker
+                .filter(|e| e.path().is_file() && filter.is_source_file(&relative(e.path())))
+                .map(|e| e.into_path()),
+        );
+    }
+
+    files
+}
+
+/// Find the key usages of all files, in parallel. Files that have not changed since they were
+/// added to `cache` are not parsed again, and the cache is updated with the others. Files that
+/// can not be read are skipped, and their errors returned with the usages.
+pub fn find_key_usages(
+    files: &[PathBuf],
+    patterns: &UsagePatterns,
+    cache: &mut UsageCache,
+) -> (Vec<KeyUsage>, Vec<TSFileError>) {
+    let scanned = files
+        .par_iter()
+        .map(|path| scan_file(path, patterns, cache.files.get(path)))
+        .collect::<Vec<_>>();
+
+    // Only keep the files that still exist in the cache
+    cache.files = HashMap::new();
+    let mut errors = Vec::new();
+    for (path, file) in files.iter().zip(scanned) {
+        match file {
+            Ok(file) => {
+                cache.files.insert(path.clone(), file);
+            }
+            Err(err) => errors.push(err),
+        }
+    }
+
+    let usages = files
+        .iter()
+        .filter_map(|path| cache.files.get(path))
+        .flat_map(|file| file.usages.iter().cloned())
+        .collect();
+    (usages, errors)
+}
+
+fn scan_file(
+    path: &Path,
+    patterns: &UsagePatterns,
+    cached: Option<&CachedFile>,
+) -> Result<CachedFile, TSFileError> {
+    let io_error = |source| TSFileError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+
+    let modified = std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map_err(io_error)?
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    if let Some(cached) = cached.filter(|cached| cached.modified == modified) {
+        return Ok(cached.clone());
+    }
+
+    let ts_file = TSFile::new(path)?;
+    let hash = format!("{:x}", Sha256::digest(ts_file.source.as_bytes()));
+    if let Some(cached) = cached.filter(|cached| cached.hash == hash) {
+        return Ok(CachedFile {
+            modified,
+            ..cached.clone()
+        });
+    }
+
+    Ok(CachedFile {
+        modified,
+        hash,
+        usages: ts_file.find_all_usages(patterns),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cached_usages() {
+        let path = std::env::temp_dir().join("ramilang_test_cached_usages.tsx");
+        std::fs::write(&path, r#"formatMessage({ id: "common.save" });"#).unwrap();
+        let files = vec![path.clone()];
+        let patterns = UsagePatterns::default();
+        let mut cache = UsageCache::new(&patterns);
+
+        let (usages, _) = find_key_usages(&files, &patterns, &mut cache);
+        assert_eq!(1, usages.len());
+        assert_eq!("common.save", usages[0].key);
+
+        // A cached file is not read again, even if it changed
+        let cached = cache.files.get_mut(&path).unwrap();
+        cached.usages[0].key = "from.cache".to_string();
+        let (usages, _) = find_key_usages(&files, &patterns, &mut cache);
+        assert_eq!("from.cache", usages[0].key);
+
+        // Or if only its modification time changed
+        cache.files.get_mut(&path).unwrap().modified = 0;
+        let (usages, _) = find_key_usages(&files, &patterns, &mut cache);
+        assert_eq!("from.cache", usages[0].key);
+
+        // But it is if its content changed too
+        let cached = cache.files.get_mut(&path).unwrap();
+        cached.modified = 0;
+        cached.hash = String::new();
+        let (usages, _) = find_key_usages(&files, &patterns, &mut cache);
+        assert_eq!("common.save", usages[0].key);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_unreadable_files_are_skipped() {
+        let path = std::env::temp_dir().join("ramilang_test_unreadable_files.tsx");
+        std::fs::write(&path, b"formatMessage({ id: \"common.save\" }); // \xff").unwrap();
+        let files = vec![path.clone(), PathBuf::from("test_files/component.tsx")];
+        let patterns = UsagePatterns::default();
+        let mut cache = UsageCache::new(&patterns);
+
+        let (usages, errors) = find_key_usages(&files, &patterns, &mut cache);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(!usages.is_empty());
+        assert_eq!(1, errors.len());
+        assert!(!cache.files.contains_key(&path));
+    }
+}
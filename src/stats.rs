@@ -0,0 +1,194 @@
+use std::fmt::Write;
+
+use console::style;
+use serde::Serialize;
+
+use crate::{
+    ignore::IgnoreList, locale::LocaleSet, translation_file::TranslationFile,
+    usage_index::UsageIndex,
+};
+
+/// Key and usage counts of a project, for `ramilang stats`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Stats {
+    pub locales: Vec<LocaleStats>,
+    /// Source files that were searched for usages
+    pub source_files: usize,
+    pub usages: usize,
+    /// Usages of dynamic keys, like `` `product_category.${code}` ``
+    pub dynamic_usages: usize,
+    /// Keys of the reference locale that are used
+    pub used_keys: usize,
+    /// Keys of the reference locale that are neither used nor ignored
+    pub unused_keys: usize,
+    pub ignored_keys: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LocaleStats {
+    pub name: String,
+    pub keys: usize,
+    /// Keys of the reference locale that this locale lacks
+    pub missing: usize,
+    pub empty: usize,
+    /// Values that are the same as in the reference locale, and not allowed to be
+    pub untranslated: usize,
+    /// Percentage of the keys of the reference locale that are really translated, with a value
+    /// that is neither empty nor untranslated
+    pub translated: f64,
+}
+
+impl Stats {
+    pub fn new(
+        locales: &LocaleSet,
+        usages: &UsageIndex,
+        ignore: &IgnoreList,
+        allowed: &[String],
+        source_files: usize,
+    ) -> Self {
+        let reference_file = &locales.reference().file;
+        let reference = &reference_file.entries;
+
+        let locale_stats = locales
+            .iter()
+            .enumerate()
+            .map(|(i, locale)| {
+                let entries = &locale.file.entries;
+                let missing = reference
+                    .keys()
+                    .filter(|key| !entries.contains_key(*key))
+                    .count();
+                let untranslated = if i == 0 {
+                    0
+                } else {
+                    locale
+                        .file
+                        .find_untranslated_values(reference_file, allowed)
+                        .len()
+                };
+                LocaleStats {
+                    name: locale.name.clone(),
+                    keys: entries.len(),
+                    missing,
+                    empty: entries.values().filter(|value| value.is_empty()).count(),
+                    untranslated,
+                    translated: translated_percentage(&locale.file, reference_file, untranslated),
+                }
+            })
+            .collect();
+
+        let used_keys = reference.keys().filter(|key| usages.is_used(key)).count();
+        let ignored_keys = reference
+            .keys()
+            .filter(|key| !usages.is_used(key) && ignore.is_ignored(key))
+            .count();
+
+        Self {
+            locales: locale_stats,
+            source_files,
+            usages: usages.len(),
+            dynamic_usages: usages.patterns().count(),
+            used_keys,
+            unused_keys: reference.len() - used_keys - ignored_keys,
+            ignored_keys,
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        let _ = writeln!(
+            text,
+            "{}",
+            style(format!(
+                "{:<10}{:>8}{:>10}{:>8}{:>15}{:>13}",
+                "LOCALE", "KEYS", "MISSING", "EMPTY", "UNTRANSLATED", "TRANSLATED"
+            ))
+            .bold()
+        );
+        for locale in &self.locales {
+            let _ = writeln!(
+                text,
+                "{:<10}{:>8}{:>10}{:>8}{:>15}{:>12.1}%",
+                locale.name,
+                locale.keys,
+                locale.missing,
+                locale.empty,
+                locale.untranslated,
+                locale.translated
+            );
+        }
+
+        let _ = writeln!(
+            text,
+            "\n{} usages ({} dynamic) in {} source files",
+            self.usages, self.dynamic_usages, self.source_files
+        );
+        let _ = writeln!(
+            text,
+            "{} keys used, {} unused and {} ignored",
+            self.used_keys, self.unused_keys, self.ignored_keys
+        );
+        text
+    }
+}
+
+/// Percentage of the keys of `reference` that `file` has a non-empty value for, not counting
+/// the `untranslated` values that are the same as in `reference`.
+pub fn translated_percentage(
+    file: &TranslationFile,
+    reference: &TranslationFile,
+    untranslated: usize,
+) -> f64 {
+    if reference.entries.is_empty() {
+        return 100.0;
+    }
+
+    let translated = reference
+        .entries
+        .keys()
+        .filter(|key| {
+            file.entries
+                .get(*key)
+                .is_some_and(|value| !value.is_empty())
+        })
+        .count();
+    translated.saturating_sub(untranslated) as f64 * 100.0 / reference.entries.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ts_file::KeyUsage;
+
+    #[test]
+    fn test_stats() {
+        let mut locales = LocaleSet::test();
+        let sv = &mut locales.get_mut("sv").unwrap().file.entries;
+        sv.insert("common.unused".to_string(), "Unused".to_string());
+        let usages = UsageIndex::new([
+            KeyUsage::test("common.save", "Button.tsx"),
+            KeyUsage {
+                dynamic: true,
+                ..KeyUsage::test("common.c*", "Button.tsx")
+            },
+        ]);
+        let mut ignore = IgnoreList::default();
+        ignore.add("common.unused", None, None).unwrap();
+
+        let stats = Stats::new(&locales, &usages, &ignore, &[], 1);
+
+        assert_eq!(2, stats.usages);
+        assert_eq!(1, stats.dynamic_usages);
+        assert_eq!(
+            (2, 1, 1),
+            (stats.used_keys, stats.unused_keys, stats.ignored_keys)
+        );
+        let sv = &stats.locales[1];
+        assert_eq!(
+            ("sv", 4, 1, 0),
+            (sv.name.as_str(), sv.keys, sv.missing, sv.empty)
+        );
+        assert_eq!(1, sv.untranslated);
+        assert_eq!(50.0, sv.translated);
+    }
+}
@@ -0,0 +1,603 @@
+//! Parses translation values as ICU MessageFormat, the syntax of react-intl's `formatMessage`.
+//!
+//! Supported are simple arguments like `{name}`, formatted arguments like `{price, number}` and
+//! `{date, date, short}`, `plural`, `selectordinal` and `select` arguments with `#` for the
+//! number, rich text tags like `<b>bold</b>` and apostrophe quoting like `'{literal}'`.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use thiserror::Error;
+
+/// A parsed message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub elements: Vec<Element>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Element {
+    Literal(String),
+    Argument(Argument),
+    /// `#` in a `plural` or `selectordinal` branch, the number of the argument
+    Pound,
+    /// `<b>...</b>`, or `<br/>` without children
+    Tag {
+        name: String,
+        children: Vec<Element>,
+    },
+}
+
+/// An argument like `{name}`, `{price, number, ::currency/EUR}` or `{count, plural, ...}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Argument {
+    pub name: String,
+    pub kind: ArgumentKind,
+    /// The style of `number`, `date` and `time` arguments, like `short`
+    pub style: Option<String>,
+    /// The branches of `plural`, `selectordinal` and `select` arguments
+    pub branches: Vec<Branch>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ArgumentKind {
+    Simple,
+    Number,
+    Date,
+    Time,
+    Plural,
+    SelectOrdinal,
+    Select,
+}
+
+/// One branch of a `plural`, `selectordinal` or `select` argument, like `one {# item}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Branch {
+    /// `one`, `other`, `=0` or any value of a `select`
+    pub selector: String,
+    pub elements: Vec<Element>,
+}
+
+/// The arguments and tags used anywhere in a message, including in branches and tags.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Placeholders {
+    /// The kind of each argument. An argument that is used both as `{n}` and for example as
+    /// `{n, number}` has the kind of the latter.
+    pub arguments: BTreeMap<String, ArgumentKind>,
+    pub tags: BTreeSet<String>,
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum MessageError {
+    /// The column is 1-based and counts characters of the value
+    #[error("{message} at column {column}")]
+    Syntax { column: usize, message: String },
+    #[error("{kind} argument \"{name}\" has no `other` branch")]
+    MissingOther { name: String, kind: ArgumentKind },
+}
+
+impl Message {
+    pub fn parse(source: &str) -> Result<Self, MessageError> {
+        let mut parser = Parser {
+            chars: source.chars().collect(),
+            position: 0,
+        };
+        let elements = parser.elements(false)?;
+        match parser.peek() {
+            None => Ok(Self { elements }),
+            Some('}') => Err(parser.error("unexpected '}'")),
+            Some(_) => Err(parser.error("unexpected closing tag")),
+        }
+    }
+
+    /// Whether the message has any letters outside of its arguments and tags, so that there is
+    /// something to translate. `{count} / {total}` has not.
+    pub fn has_text(&self) -> bool {
+        has_text(&self.elements)
+    }
+
+    pub fn placeholders(&self) -> Placeholders {
+        let mut placeholders = Placeholders::default();
+        add_placeholders(&self.elements, &mut placeholders);
+        placeholders
+    }
+}
+
+fn has_text(elements: &[Element]) -> bool {
+    elements.iter().any(|element| match element {
+        Element::Literal(text) => text.chars().any(char::is_alphabetic),
+        Element::Argument(argument) => argument
+            .branches
+            .iter()
+            .any(|branch| has_text(&branch.elements)),
+        Element::Tag { children, .. } => has_text(children),
+        Element::Pound => false,
+    })
+}
+
+fn add_placeholders(elements: &[Element], placeholders: &mut Placeholders) {
+    for element in elements {
+        match element {
+            Element::Argument(argument) => {
+                let kind = placeholders
+                    .arguments
+                    .entry(argument.name.clone())
+                    .or_insert(argument.kind);
+                if *kind == ArgumentKind::Simple {
+                    *kind = argument.kind;
+                }
+                for branch in &argument.branches {
+                    add_placeholders(&branch.elements, placeholders);
+                }
+            }
+            Element::Tag { name, children } => {
+                placeholders.tags.insert(name.clone());
+                add_placeholders(children, placeholders);
+            }
+            Element::Literal(_) | Element::Pound => {}
+        }
+    }
+}
+
+impl ArgumentKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArgumentKind::Simple => "simple",
+            ArgumentKind::Number => "number",
+            ArgumentKind::Date => "date",
+            ArgumentKind::Time => "time",
+            ArgumentKind::Plural => "plural",
+            ArgumentKind::SelectOrdinal => "selectordinal",
+            ArgumentKind::Select => "select",
+        }
+    }
+
+    fn has_branches(&self) -> bool {
+        matches!(
+            self,
+            ArgumentKind::Plural | ArgumentKind::SelectOrdinal | ArgumentKind::Select
+        )
+    }
+}
+
+impl fmt::Display for ArgumentKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn peek_next(&self) -> Option<char> {
+        self.chars.get(self.position + 1).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        Some(c)
+    }
+
+    fn error(&self, message: &str) -> MessageError {
+        let message = match self.peek() {
+            Some(_) => message.to_string(),
+            None => format!("{}, found the end of the message", message),
+        };
+        MessageError::Syntax {
+            column: self.position + 1,
+            message,
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), MessageError> {
+        if self.peek() != Some(expected) {
+            return Err(self.error(&format!("expected '{}'", expected)));
+        }
+        self.bump();
+        Ok(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    /// Elements until the end of the message, a `}` or a closing tag, which are left for the
+    /// caller. `#` is only special directly in a plural branch.
+    fn elements(&mut self, in_plural: bool) -> Result<Vec<Element>, MessageError> {
+        let mut elements = Vec::new();
+        let mut literal = String::new();
+        loop {
+            let element = match self.peek() {
+                None | Some('}') => break,
+                Some('<') if self.peek_next() == Some('/') => break,
+                Some('{') => Element::Argument(self.argument()?),
+                Some('#') if in_plural => {
+                    self.bump();
+                    Element::Pound
+                }
+                Some('<') if self.peek_next().is_some_and(|c| c.is_ascii_alphabetic()) => {
+                    self.tag(in_plural)?
+                }
+                Some('\'') => {
+                    self.quoted(in_plural, &mut literal);
+                    continue;
+                }
+                Some(c) => {
+                    self.bump();
+                    literal.push(c);
+                    continue;
+                }
+            };
+            if !literal.is_empty() {
+                elements.push(Element::Literal(std::mem::take(&mut literal)));
+            }
+            elements.push(element);
+        }
+
+        if !literal.is_empty() {
+            elements.push(Element::Literal(literal));
+        }
+        Ok(elements)
+    }
+
+    /// An apostrophe, which quotes the syntax characters that follow it until the next single
+    /// apostrophe. `''` is always an apostrophe.
+    fn quoted(&mut self, in_plural: bool, literal: &mut String) {
+        self.bump();
+        match self.peek() {
+            Some('\'') => {
+                self.bump();
+                literal.push('\'');
+                return;
+            }
+            Some('{' | '}' | '<' | '>') => {}
+            Some('#') if in_plural => {}
+            _ => {
+                literal.push('\'');
+                return;
+            }
+        }
+
+        while let Some(c) = self.bump() {
+            if c != '\'' {
+                literal.push(c);
+            } else if self.peek() == Some('\'') {
+                self.bump();
+                literal.push('\'');
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn argument(&mut self) -> Result<Argument, MessageError> {
+        self.expect('{')?;
+        self.skip_whitespace();
+        let name = self.identifier();
+        if name.is_empty() {
+            return Err(self.error("expected an argument name"));
+        }
+        self.skip_whitespace();
+
+        let mut argument = Argument {
+            name,
+            kind: ArgumentKind::Simple,
+            style: None,
+            branches: Vec::new(),
+        };
+        if self.peek() == Some('}') {
+            self.bump();
+            return Ok(argument);
+        }
+        if self.peek() != Some(',') {
+            return Err(self.error("expected ',' or '}'"));
+        }
+        self.bump();
+        self.skip_whitespace();
+
+        let type_column = self.position;
+        argument.kind = match self.identifier().as_str() {
+            "number" => ArgumentKind::Number,
+            "date" => ArgumentKind::Date,
+            "time" => ArgumentKind::Time,
+            "plural" => ArgumentKind::Plural,
+            "selectordinal" => ArgumentKind::SelectOrdinal,
+            "select" => ArgumentKind::Select,
+            "" => return Err(self.error("expected an argument type")),
+            other => {
+                self.position = type_column;
+                return Err(self.error(&format!("unknown argument type \"{}\"", other)));
+            }
+        };
+        self.skip_whitespace();
+
+        if argument.kind.has_branches() {
+            self.expect(',')?;
+            argument.branches = self.branches(argument.kind)?;
+            if !argument
+                .branches
+                .iter()
+                .any(|branch| branch.selector == "other")
+            {
+                return Err(MessageError::MissingOther {
+                    name: argument.name,
+                    kind: argument.kind,
+                });
+            }
+        } else if self.peek() == Some(',') {
+            self.bump();
+            argument.style = Some(self.style()?);
+        }
+
+        self.expect('}')?;
+        Ok(argument)
+    }
+
+    fn branches(&mut self, kind: ArgumentKind) -> Result<Vec<Branch>, MessageError> {
+        let in_plural = kind != ArgumentKind::Select;
+        self.skip_whitespace();
+        if in_plural && self.chars[self.position..].starts_with(&['o', 'f', 'f', 's', 'e', 't']) {
+            self.position += "offset".len();
+            self.skip_whitespace();
+            self.expect(':')?;
+            self.skip_whitespace();
+            if !self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                return Err(self.error("expected a number"));
+            }
+            while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.bump();
+            }
+        }
+
+        let mut branches: Vec<Branch> = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some('}') && !branches.is_empty() {
+                return Ok(branches);
+            }
+
+            let selector_column = self.position;
+            let selector = match self.peek() {
+                Some('=') if in_plural => {
+                    self.bump();
+                    format!("={}", self.identifier())
+                }
+                _ => self.identifier(),
+            };
+            if selector.is_empty() || selector == "=" {
+                self.position = selector_column;
+                return Err(self.error("expected a selector"));
+            }
+            if branches.iter().any(|branch| branch.selector == selector) {
+                self.position = selector_column;
+                return Err(self.error(&format!("duplicate selector \"{}\"", selector)));
+            }
+
+            self.skip_whitespace();
+            self.expect('{')?;
+            let elements = self.elements(in_plural)?;
+            self.expect('}')?;
+            branches.push(Branch { selector, elements });
+        }
+    }
+
+    /// The style of a `number`, `date` or `time` argument, up to the closing `}`.
+    fn style(&mut self) -> Result<String, MessageError> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c != '}' && c != '{') {
+            self.bump();
+        }
+        let style = self.chars[start..self.position]
+            .iter()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        if style.is_empty() {
+            return Err(self.error("expected an argument style"));
+        }
+        Ok(style)
+    }
+
+    /// `<name>children</name>` or `<name/>`.
+    fn tag(&mut self, in_plural: bool) -> Result<Element, MessageError> {
+        self.expect('<')?;
+        let name = self.tag_name();
+        if self.peek() == Some('/') {
+            self.bump();
+            self.expect('>')?;
+            return Ok(Element::Tag {
+                name,
+                children: Vec::new(),
+            });
+        }
+        self.expect('>')?;
+
+        let children = self.elements(in_plural)?;
+        if self.peek() != Some('<') {
+            return Err(self.error(&format!("expected </{}>", name)));
+        }
+        let closing_column = self.position;
+        self.bump();
+        self.expect('/')?;
+        let closing = self.tag_name();
+        if closing != name {
+            self.position = closing_column;
+            return Err(self.error(&format!("expected </{}>, found </{}>", name, closing)));
+        }
+        self.expect('>')?;
+
+        Ok(Element::Tag { name, children })
+    }
+
+    /// An argument name, argument type or selector.
+    fn identifier(&mut self) -> String {
+        let mut identifier = String::new();
+        while let Some(c) = self
+            .peek()
+            .filter(|c| !c.is_whitespace() && !"{}<>,=#'/:".contains(*c))
+        {
+            self.bump();
+            identifier.push(c);
+        }
+        identifier
+    }
+
+    fn tag_name(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(c) = self
+            .peek()
+            .filter(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+        {
+            self.bump();
+            name.push(c);
+        }
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn syntax_error(source: &str) -> (usize, String) {
+        match Message::parse(source) {
+            Err(MessageError::Syntax { column, message }) => (column, message),
+            other => panic!("expected a syntax error for {:?}, got {:?}", source, other),
+        }
+    }
+
+    #[test]
+    fn test_parse_message() {
+        let message = Message::parse(
+            "Hi <b>{name}</b>, {count, plural, =0 {no items} one {# item} other {# items}} since {date, date, short}. It''s '{literal}'",
+        )
+        .unwrap();
+
+        let arguments = message
+            .elements
+            .iter()
+            .filter_map(|element| match element {
+                Element::Argument(argument) => Some((argument.name.as_str(), argument.kind)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("count", ArgumentKind::Plural),
+                ("date", ArgumentKind::Date)
+            ],
+            arguments
+        );
+        assert_eq!(
+            Element::Tag {
+                name: "b".to_string(),
+                children: vec![Element::Argument(Argument {
+                    name: "name".to_string(),
+                    kind: ArgumentKind::Simple,
+                    style: None,
+                    branches: Vec::new(),
+                })],
+            },
+            message.elements[1]
+        );
+        assert_eq!(
+            Some(&Element::Literal(" since ".to_string())),
+            message.elements.get(4)
+        );
+        assert_eq!(
+            Some(&Element::Literal(". It's {literal}".to_string())),
+            message.elements.last()
+        );
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let message = Message::parse(
+            "{n} <b>{n, plural, one {<i>{name}</i>} other {# {gender, select, other {}}}}</b>",
+        )
+        .unwrap();
+        let placeholders = message.placeholders();
+
+        assert_eq!(
+            vec![
+                ("gender", ArgumentKind::Select),
+                ("n", ArgumentKind::Plural),
+                ("name", ArgumentKind::Simple)
+            ],
+            placeholders
+                .arguments
+                .iter()
+                .map(|(name, kind)| (name.as_str(), *kind))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(vec!["b", "i"], placeholders.tags.iter().collect::<Vec<_>>());
+        assert!(!message.has_text());
+        assert!(Message::parse("{n, plural, other {# items}}")
+            .unwrap()
+            .has_text());
+        assert!(!Message::parse("{n} / <b>{total}</b>").unwrap().has_text());
+    }
+
+    #[test]
+    fn test_pound_and_literals() {
+        let message = Message::parse("#1 < 2 {n, plural, other {# <i>#</i>}}").unwrap();
+        assert_eq!(Element::Literal("#1 < 2 ".to_string()), message.elements[0]);
+        let Element::Argument(argument) = &message.elements[1] else {
+            panic!("expected an argument");
+        };
+        assert_eq!(Element::Pound, argument.branches[0].elements[0]);
+        assert!(Message::parse("{gender, select, other {#}}").is_ok());
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        assert_eq!(
+            (
+                12,
+                "expected ',' or '}', found the end of the message".to_string()
+            ),
+            syntax_error("Hello {name")
+        );
+        assert_eq!((1, "unexpected '}'".to_string()), syntax_error("}"));
+        assert_eq!(
+            (10, "unknown argument type \"nummer\"".to_string()),
+            syntax_error("{amount, nummer}")
+        );
+        assert_eq!(
+            (8, "expected </b>, found </i>".to_string()),
+            syntax_error("<b>bold</i>")
+        );
+        assert_eq!(
+            (31, "duplicate selector \"one\"".to_string()),
+            syntax_error("{n, plural, one {a} other {b} one {c}}")
+        );
+        assert_eq!(
+            (5, "unexpected closing tag".to_string()),
+            syntax_error("bold</b>")
+        );
+    }
+
+    #[test]
+    fn test_missing_other() {
+        assert_eq!(
+            Err(MessageError::MissingOther {
+                name: "count".to_string(),
+                kind: ArgumentKind::Plural
+            }),
+            Message::parse("{count, plural, one {# item} few {# items}}")
+        );
+        assert!(Message::parse("{gender, select, male {He} female {She}}").is_err());
+    }
+}
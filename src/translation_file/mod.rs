@@ -0,0 +1,784 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs::File,
+    io::Write,
+    path::PathBuf,
+};
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::{ser::PrettyFormatter, Map, Serializer, Value};
+use thiserror::Error;
+
+mod message;
+mod scanner;
+
+pub use message::{Argument, ArgumentKind, Branch, Element, Message, MessageError, Placeholders};
+pub use scanner::{DuplicateKey, KeyOccurrence};
+
+/// Separates the parts of a nested key, `{ "common": { "save": "Save" } }` is `common.save`.
+pub static KEY_SEPARATOR: char = '.';
+
+#[derive(Debug, Clone)]
+pub struct TranslationFile {
+    pub path: PathBuf,
+    /// All translations, with the keys of nested files flattened into dotted keys
+    pub entries: BTreeMap<String, String>,
+    /// Keys that are defined more than once in the file, only the last value is kept in `entries`
+    pub duplicate_keys: Vec<DuplicateKey>,
+    /// Shape the file is written in
+    pub shape: Shape,
+    layout: Layout,
+}
+
+/// How the file looked when it was read, so that writing it back only changes what was edited.
+#[derive(Debug, Clone)]
+struct Layout {
+    /// The content of the file, for the order and nesting of the keys
+    source: Map<String, Value>,
+    shape: Shape,
+    /// Indentation of one level, `None` if the file is minified
+    indent: Option<String>,
+    line_ending: &'static str,
+    trailing_newline: bool,
+    /// String literals of the values as written, to keep escapes such as `\u00e9` of values
+    /// that are not changed
+    raw_values: HashMap<String, String>,
+}
+
+/// How the keys of a translation file are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Shape {
+    /// `{ "common.save": "Save" }`
+    Flat,
+    /// `{ "common": { "save": "Save" } }`
+    Nested,
+}
+
+#[derive(Error, Debug)]
+pub enum TranslationFileError {
+    #[error("could not read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid JSON in {path}:{line}:{column}: {message}")]
+    Parse {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    #[error("key \"{key}\" is empty in {path}")]
+    EmptyValue { key: String, path: PathBuf },
+    #[error("key \"{key}\" is missing from {missing_in}")]
+    MissingKey { key: String, missing_in: PathBuf },
+    #[error("duplicate keys found in {0}, keys: {}", .1.iter().map(|duplicate| duplicate.key.as_str()).collect::<Vec<_>>().join(", "))]
+    DuplicateKeys(PathBuf, Vec<DuplicateKey>),
+    #[error("value of key \"{key}\" in {path} is not a string or an object")]
+    InvalidValue { key: String, path: PathBuf },
+    #[error("value of key \"{key}\" in {path} is not a valid ICU message: {source}")]
+    InvalidMessage {
+        key: String,
+        path: PathBuf,
+        source: MessageError,
+    },
+    #[error("key \"{key}\" in {path} does not use the argument {{{argument}}} of {reference}")]
+    MissingArgument {
+        key: String,
+        argument: String,
+        path: PathBuf,
+        reference: PathBuf,
+    },
+    #[error(
+        "key \"{key}\" in {path} uses the argument {{{argument}}}, which {reference} does not have"
+    )]
+    UnknownArgument {
+        key: String,
+        argument: String,
+        path: PathBuf,
+        reference: PathBuf,
+    },
+    #[error("argument {{{argument}}} of key \"{key}\" is a {found} argument in {path}, but a {expected} argument in {reference}")]
+    ArgumentTypeMismatch {
+        key: String,
+        argument: String,
+        expected: ArgumentKind,
+        found: ArgumentKind,
+        path: PathBuf,
+        reference: PathBuf,
+    },
+    #[error("key \"{key}\" in {path} does not have the tag <{tag}> of {reference}")]
+    MissingTag {
+        key: String,
+        tag: String,
+        path: PathBuf,
+        reference: PathBuf,
+    },
+    #[error("key \"{key}\" in {path} has the tag <{tag}>, which {reference} does not have")]
+    UnknownTag {
+        key: String,
+        tag: String,
+        path: PathBuf,
+        reference: PathBuf,
+    },
+    #[error("key \"{key}\" in {path} has the same value as in {reference} (\"{value}\")")]
+    UntranslatedValue {
+        key: String,
+        value: String,
+        path: PathBuf,
+        reference: PathBuf,
+    },
+    #[error("key \"{key}\" in {path} was translated from an earlier value of {reference}")]
+    StaleTranslation {
+        key: String,
+        path: PathBuf,
+        reference: PathBuf,
+    },
+    #[error("key \"{key}\" in {path} is both a translation and a prefix of other keys, it can not be nested")]
+    NestingConflict { key: String, path: PathBuf },
+}
+
+impl TranslationFile {
+    pub fn new(path: PathBuf) -> Result<Self, TranslationFileError> {
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Self::parse(path, &content),
+            Err(source) => Err(TranslationFileError::Io { path, source }),
+        }
+    }
+
+    /// A translation file with `content`, for example an earlier version of the file at `path`.
+    pub fn parse(path: PathBuf, content: &str) -> Result<Self, TranslationFileError> {
+        let root: Map<String, Value> = match serde_json::from_str(content) {
+            Ok(root) => root,
+            Err(err) => {
+                return Err(TranslationFileError::Parse {
+                    path,
+                    line: err.line(),
+                    column: err.column(),
+                    message: parse_error_message(&err),
+                })
+            }
+        };
+        let scan = scanner::scan(content);
+
+        let mut entries = BTreeMap::new();
+        flatten(&root, "", &path, &mut entries)?;
+        let mut layout = Layout::detect(content, root);
+        layout.raw_values = scan.raw_values;
+
+        Ok(Self {
+            path,
+            entries,
+            duplicate_keys: scan.duplicate_keys,
+            shape: layout.shape,
+            layout,
+        })
+    }
+
+    /// Keys that are defined more than once in the file.
+    pub fn find_duplicate_keys(&self) -> Option<TranslationFileError> {
+        if self.duplicate_keys.is_empty() {
+            return None;
+        }
+
+        Some(TranslationFileError::DuplicateKeys(
+            self.path.clone(),
+            self.duplicate_keys.clone(),
+        ))
+    }
+
+    /// Compare two translation files and return an error if they are not compatible.
+    ///
+    /// Two translation files are compatible if:
+    /// - They have the same keys
+    /// - All keys have a non-empty value
+    /// - All values are valid ICU messages
+    /// - The values of `other` use the same arguments, of the same types, and the same tags as
+    ///   the values of `self`, which is the reference
+    pub fn is_compatible_with(
+        &self,
+        other: &Self,
+    ) -> Result<(), (Vec<TranslationFileError>, Vec<TranslationFileError>)> {
+        let self_errors = self.check_rules(other);
+        let mut other_errors = other.check_rules(self);
+        other_errors.extend(other.find_placeholder_mismatches(self));
+
+        if !self_errors.is_empty() || !other_errors.is_empty() {
+            return Err((self_errors, other_errors));
+        }
+
+        Ok(())
+    }
+
+    /// Write the entries back to the file. Keys keep their order and nesting, and the file keeps
+    /// its indentation, line endings and trailing newline. New keys are added last.
+    pub fn write(&self) -> Result<()> {
+        let serialized_entries = self.serialize()?;
+
+        let mut file = File::create(&self.path)?;
+        Ok(file.write_all(serialized_entries.as_bytes())?)
+    }
+
+    /// The content `write` writes to the file.
+    pub fn serialize(&self) -> Result<String> {
+        let mut root = self.to_json()?;
+        let mut literals = Vec::new();
+        raw_placeholders(&mut root, "", &self.layout.raw_values, &mut literals);
+        let root = Value::Object(root);
+
+        let mut serialized_entries = match &self.layout.indent {
+            Some(indent) => {
+                let mut buffer = Vec::new();
+                let formatter = PrettyFormatter::with_indent(indent.as_bytes());
+                root.serialize(&mut Serializer::with_formatter(&mut buffer, formatter))?;
+                String::from_utf8(buffer)?
+            }
+            None => serde_json::to_string(&root)?,
+        };
+        for (i, literal) in literals.iter().enumerate() {
+            let placeholder = serde_json::to_string(&raw_placeholder(i))?;
+            serialized_entries = serialized_entries.replacen(&placeholder, literal, 1);
+        }
+        if self.layout.trailing_newline {
+            serialized_entries.push('\n');
+        }
+        // Line breaks in values are escaped, so these are all line breaks between entries
+        Ok(serialized_entries.replace('\n', self.layout.line_ending))
+    }
+
+    /// Sort the keys alphabetically, on every level of nesting, the next time the file is written.
+    pub fn sort(&mut self) -> Result<(), TranslationFileError> {
+        self.layout.source = sort_keys(self.to_json()?);
+        self.layout.shape = self.shape;
+        Ok(())
+    }
+
+    /// The entries as they are written to the file, in the order they were read in and with the
+    /// keys of nested files split on their dots.
+    pub fn to_json(&self) -> Result<Map<String, Value>, TranslationFileError> {
+        let mut written = HashSet::new();
+        let mut root = if self.shape == self.layout.shape {
+            retain_entries(&self.layout.source, "", &self.entries, &mut written)
+        } else {
+            Map::new()
+        };
+
+        // Add new keys, and all keys if the shape was changed, in the order they were read in
+        let mut keys = Vec::new();
+        leaf_keys(&self.layout.source, "", &mut keys);
+        keys.extend(self.entries.keys().cloned());
+
+        for key in keys {
+            let Some(value) = self.entries.get(&key) else {
+                continue;
+            };
+            if !written.insert(key.clone()) {
+                continue;
+            }
+
+            match self.shape {
+                Shape::Flat => {
+                    root.insert(key, Value::String(value.clone()));
+                }
+                Shape::Nested => insert_nested(&mut root, &key, value).map_err(|_| {
+                    TranslationFileError::NestingConflict {
+                        key: key.clone(),
+                        path: self.path.clone(),
+                    }
+                })?,
+            }
+        }
+
+        Ok(root)
+    }
+
+    /// Keys of this file that are not present in `other`.
+    pub fn find_missing_keys(&self, other: &Self) -> Vec<TranslationFileError> {
+        self.entries
+            .keys()
+            .filter(|key| !other.entries.contains_key(*key))
+            .map(|key| TranslationFileError::MissingKey {
+                key: key.clone(),
+                missing_in: other.path.clone(),
+            })
+            .collect()
+    }
+
+    /// Keys of this file that have an empty value.
+    pub fn find_empty_values(&self) -> Vec<TranslationFileError> {
+        self.entries
+            .iter()
+            .filter(|(_, value)| value.is_empty())
+            .map(|(key, _)| TranslationFileError::EmptyValue {
+                key: key.clone(),
+                path: self.path.clone(),
+            })
+            .collect()
+    }
+
+    /// Values that are not valid ICU MessageFormat messages, or have a plural or select without
+    /// an `other` branch.
+    pub fn find_invalid_messages(&self) -> Vec<TranslationFileError> {
+        self.entries
+            .iter()
+            .filter_map(|(key, value)| {
+                let source = Message::parse(value).err()?;
+                Some(TranslationFileError::InvalidMessage {
+                    key: key.clone(),
+                    path: self.path.clone(),
+                    source,
+                })
+            })
+            .collect()
+    }
+
+    /// Values whose arguments or tags differ from those of the same key in `reference`. Keys that
+    /// only one of the files has, and values that are empty or invalid, are not compared.
+    pub fn find_placeholder_mismatches(&self, reference: &Self) -> Vec<TranslationFileError> {
+        let mut errors = Vec::new();
+        for (key, value) in &self.entries {
+            let Some(reference_value) = reference.entries.get(key) else {
+                continue;
+            };
+            if value.is_empty() || reference_value.is_empty() {
+                continue;
+            }
+            let (Ok(message), Ok(reference_message)) =
+                (Message::parse(value), Message::parse(reference_value))
+            else {
+                continue;
+            };
+            let placeholders = message.placeholders();
+            let expected = reference_message.placeholders();
+
+            for (argument, expected_kind) in &expected.arguments {
+                match placeholders.arguments.get(argument) {
+                    None => errors.push(TranslationFileError::MissingArgument {
+                        key: key.clone(),
+                        argument: argument.clone(),
+                        path: self.path.clone(),
+                        reference: reference.path.clone(),
+                    }),
+                    Some(kind) if kind != expected_kind => {
+                        errors.push(TranslationFileError::ArgumentTypeMismatch {
+                            key: key.clone(),
+                            argument: argument.clone(),
+                            expected: *expected_kind,
+                            found: *kind,
+                            path: self.path.clone(),
+                            reference: reference.path.clone(),
+                        })
+                    }
+                    Some(_) => {}
+                }
+            }
+            for argument in placeholders.arguments.keys() {
+                if !expected.arguments.contains_key(argument) {
+                    errors.push(TranslationFileError::UnknownArgument {
+                        key: key.clone(),
+                        argument: argument.clone(),
+                        path: self.path.clone(),
+                        reference: reference.path.clone(),
+                    });
+                }
+            }
+            for tag in expected.tags.difference(&placeholders.tags) {
+                errors.push(TranslationFileError::MissingTag {
+                    key: key.clone(),
+                    tag: tag.clone(),
+                    path: self.path.clone(),
+                    reference: reference.path.clone(),
+                });
+            }
+            for tag in placeholders.tags.difference(&expected.tags) {
+                errors.push(TranslationFileError::UnknownTag {
+                    key: key.clone(),
+                    tag: tag.clone(),
+                    path: self.path.clone(),
+                    reference: reference.path.clone(),
+                });
+            }
+        }
+
+        errors
+    }
+
+    /// Keys whose value is the same as in `reference`, which is usually a copy that was never
+    /// translated. Values in `allowed`, like brand names, and values without any text to
+    /// translate, like `{count}`, are skipped.
+    pub fn find_untranslated_values(
+        &self,
+        reference: &Self,
+        allowed: &[String],
+    ) -> Vec<TranslationFileError> {
+        self.entries
+            .iter()
+            .filter(|(key, value)| {
+                reference.entries.get(*key) == Some(value)
+                    && !allowed.contains(value)
+                    && Message::parse(value).is_ok_and(|message| message.has_text())
+            })
+            .map(|(key, value)| TranslationFileError::UntranslatedValue {
+                key: key.clone(),
+                value: value.clone(),
+                path: self.path.clone(),
+                reference: reference.path.clone(),
+            })
+            .collect()
+    }
+
+    fn check_rules(&self, other: &Self) -> Vec<TranslationFileError> {
+        let mut errors = self.find_missing_keys(other);
+        errors.extend(self.find_empty_values());
+        errors.extend(self.find_invalid_messages());
+
+        errors
+    }
+}
+
+/// The message of a `serde_json` error without the position, which is reported separately.
+fn parse_error_message(err: &serde_json::Error) -> String {
+    let message = err.to_string();
+    match message.rfind(" at line ") {
+        Some(index) => message[..index].to_string(),
+        None => message,
+    }
+}
+
+impl Layout {
+    fn detect(content: &str, source: Map<String, Value>) -> Self {
+        let shape = if source.values().any(Value::is_object) {
+            Shape::Nested
+        } else {
+            Shape::Flat
+        };
+
+        // The indentation of the first indented line is one level
+        let indent = content
+            .lines()
+            .find(|line| line.starts_with([' ', '\t']) && !line.trim().is_empty())
+            .map(|line| line[..line.len() - line.trim_start().len()].to_string());
+        let indent = match indent {
+            Some(indent) => Some(indent),
+            None if source.is_empty() => Some("  ".to_string()),
+            None => None,
+        };
+
+        Self {
+            source,
+            shape,
+            indent,
+            line_ending: if content.contains("\r\n") {
+                "\r\n"
+            } else {
+                "\n"
+            },
+            trailing_newline: content.ends_with('\n'),
+            raw_values: HashMap::new(),
+        }
+    }
+}
+
+/// The parts of `source` whose keys are still in `entries`, with the values from `entries`.
+fn retain_entries(
+    source: &Map<String, Value>,
+    prefix: &str,
+    entries: &BTreeMap<String, String>,
+    written: &mut HashSet<String>,
+) -> Map<String, Value> {
+    let mut retained = Map::new();
+    for (name, value) in source {
+        let key = join_key(prefix, name);
+        match value {
+            Value::Object(child) => {
+                let child = retain_entries(child, &key, entries, written);
+                if !child.is_empty() {
+                    retained.insert(name.clone(), Value::Object(child));
+                }
+            }
+            _ => {
+                if let Some(value) = entries.get(&key) {
+                    retained.insert(name.clone(), Value::String(value.clone()));
+                    written.insert(key);
+                }
+            }
+        }
+    }
+
+    retained
+}
+
+/// The flattened keys of `source`, in order.
+fn leaf_keys(source: &Map<String, Value>, prefix: &str, keys: &mut Vec<String>) {
+    for (name, value) in source {
+        let key = join_key(prefix, name);
+        match value {
+            Value::Object(child) => leaf_keys(child, &key, keys),
+            _ => keys.push(key),
+        }
+    }
+}
+
+/// Insert a dotted key as nested objects, fails if a part of the key is already a translation.
+fn insert_nested(root: &mut Map<String, Value>, key: &str, value: &str) -> Result<(), ()> {
+    let mut parts = key.split(KEY_SEPARATOR).peekable();
+    let mut object = root;
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            if object.contains_key(part) {
+                return Err(());
+            }
+            object.insert(part.to_string(), Value::String(value.to_string()));
+            break;
+        }
+
+        object = match object
+            .entry(part)
+            .or_insert_with(|| Value::Object(Map::new()))
+        {
+            Value::Object(child) => child,
+            _ => return Err(()),
+        };
+    }
+
+    Ok(())
+}
+
+/// Replace the values of `object` that are unchanged since the file was read, but that
+/// `serde_json` would write differently, with placeholders for their literal in `raw_values`.
+fn raw_placeholders(
+    object: &mut Map<String, Value>,
+    prefix: &str,
+    raw_values: &HashMap<String, String>,
+    literals: &mut Vec<String>,
+) {
+    for (name, value) in object.iter_mut() {
+        let key = join_key(prefix, name);
+        match value {
+            Value::Object(child) => raw_placeholders(child, &key, raw_values, literals),
+            Value::String(string) => {
+                let Some(raw) = raw_values.get(&key) else {
+                    continue;
+                };
+                let unchanged =
+                    serde_json::from_str::<String>(raw).is_ok_and(|original| original == *string);
+                if unchanged && serde_json::to_string(string).ok().as_ref() != Some(raw) {
+                    *string = raw_placeholder(literals.len());
+                    literals.push(raw.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A value that does not occur in translation files, characters of the private use area are
+/// written as they are.
+fn raw_placeholder(i: usize) -> String {
+    format!("\u{e000}ramilang-raw-{}\u{e000}", i)
+}
+
+fn sort_keys(object: Map<String, Value>) -> Map<String, Value> {
+    let mut entries = object.into_iter().collect::<Vec<_>>();
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    entries
+        .into_iter()
+        .map(|(key, value)| match value {
+            Value::Object(child) => (key, Value::Object(sort_keys(child))),
+            value => (key, value),
+        })
+        .collect()
+}
+
+fn join_key(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}{}{}", prefix, KEY_SEPARATOR, name)
+    }
+}
+
+/// Add the string values of `object` to `entries`, with the keys of nested objects joined by dots.
+fn flatten(
+    object: &Map<String, Value>,
+    prefix: &str,
+    path: &PathBuf,
+    entries: &mut BTreeMap<String, String>,
+) -> Result<(), TranslationFileError> {
+    for (key, value) in object {
+        let key = join_key(prefix, key);
+
+        match value {
+            Value::String(value) => {
+                entries.insert(key, value.clone());
+            }
+            Value::Object(child) => flatten(child, &key, path, entries)?,
+            _ => {
+                return Err(TranslationFileError::InvalidValue {
+                    key,
+                    path: path.clone(),
+                })
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_nested_file() {
+        let file = TranslationFile::new(PathBuf::from("test_files/nested/en.json")).unwrap();
+
+        assert_eq!(Shape::Nested, file.shape);
+        assert_eq!(
+            Some(&"Save".to_string()),
+            file.entries.get("common.actions.save")
+        );
+        assert_eq!(Some(&"Name".to_string()), file.entries.get("name"));
+        assert_eq!(4, file.entries.len());
+        assert!(file.duplicate_keys.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_files() {
+        let path = std::env::temp_dir().join("ramilang_test_invalid_files.json");
+        std::fs::write(&path, "{\n  \"a\": \"A\",\n  \"b\" \"B\"\n}\n").unwrap();
+        let err = TranslationFile::new(path.clone()).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(
+            err,
+            TranslationFileError::Parse {
+                line: 3,
+                column: 7,
+                ..
+            }
+        ));
+        assert!(matches!(
+            TranslationFile::new(path),
+            Err(TranslationFileError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn test_to_json() {
+        let mut file = TranslationFile::new(PathBuf::from("test_files/nested/en.json")).unwrap();
+        let expected: Value =
+            serde_json::from_str(&std::fs::read_to_string("test_files/nested/en.json").unwrap())
+                .unwrap();
+        assert_eq!(expected, Value::Object(file.to_json().unwrap()));
+
+        file.shape = Shape::Flat;
+        let flat = file.to_json().unwrap();
+        assert_eq!(
+            vec![
+                "common.actions.cancel",
+                "common.actions.save",
+                "common.title",
+                "name"
+            ],
+            flat.keys().collect::<Vec<_>>()
+        );
+
+        file.shape = Shape::Nested;
+        file.entries
+            .insert("common.actions".to_string(), "Actions".to_string());
+        assert!(matches!(
+            file.to_json(),
+            Err(TranslationFileError::NestingConflict { .. })
+        ));
+    }
+
+    #[test]
+    fn test_write_keeps_layout() {
+        let path = std::env::temp_dir().join("ramilang_test_write_keeps_layout.json");
+        std::fs::write(
+            &path,
+            "{\r\n    \"b\": \"B\",\r\n    \"a\": \"A\",\r\n    \"c\": \"C\"\r\n}\r\n",
+        )
+        .unwrap();
+        let mut file = TranslationFile::new(path.clone()).unwrap();
+
+        file.entries.insert("a".to_string(), "Changed".to_string());
+        file.entries.remove("c");
+        file.entries.insert("0".to_string(), "New".to_string());
+        file.write().unwrap();
+        assert_eq!(
+            "{\r\n    \"b\": \"B\",\r\n    \"a\": \"Changed\",\r\n    \"0\": \"New\"\r\n}\r\n",
+            std::fs::read_to_string(&path).unwrap()
+        );
+
+        file.sort().unwrap();
+        file.write().unwrap();
+        assert_eq!(
+            "{\r\n    \"0\": \"New\",\r\n    \"a\": \"Changed\",\r\n    \"b\": \"B\"\r\n}\r\n",
+            std::fs::read_to_string(&path).unwrap()
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_serialize_keeps_escapes() {
+        let content = "{\n  \"a\": \"Caf\\u00e9 \\/ \\u00e9\",\n  \"b\": \"B\\u00e9\"\n}\n";
+        let mut file = TranslationFile::parse(PathBuf::from("fr.json"), content).unwrap();
+        assert_eq!("Café / é", file.entries["a"]);
+
+        file.entries.insert("b".to_string(), "Bä".to_string());
+        assert_eq!(
+            "{\n  \"a\": \"Caf\\u00e9 \\/ \\u00e9\",\n  \"b\": \"Bä\"\n}\n",
+            file.serialize().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_placeholder_mismatches() {
+        let en = TranslationFile::new(PathBuf::from("test_files/placeholders/en.json")).unwrap();
+        let sv = TranslationFile::new(PathBuf::from("test_files/placeholders/sv.json")).unwrap();
+
+        let errors = sv.find_placeholder_mismatches(&en);
+        let mismatches = errors
+            .iter()
+            .map(|error| match error {
+                TranslationFileError::MissingArgument { key, argument, .. } => {
+                    format!("{}: missing {{{}}}", key, argument)
+                }
+                TranslationFileError::UnknownArgument { key, argument, .. } => {
+                    format!("{}: unknown {{{}}}", key, argument)
+                }
+                TranslationFileError::ArgumentTypeMismatch {
+                    key,
+                    argument,
+                    expected,
+                    found,
+                    ..
+                } => format!("{}: {{{}}} is {} not {}", key, argument, found, expected),
+                TranslationFileError::MissingTag { key, tag, .. } => {
+                    format!("{}: missing <{}>", key, tag)
+                }
+                TranslationFileError::UnknownTag { key, tag, .. } => {
+                    format!("{}: unknown <{}>", key, tag)
+                }
+                error => panic!("unexpected error {}", error),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                "greeting: missing {name}",
+                "greeting: unknown {namn}",
+                "items: {count} is simple not plural",
+                "terms: missing <link>",
+                "terms: unknown <b>",
+            ],
+            mismatches
+        );
+        assert!(en.is_compatible_with(&sv).is_err());
+        assert!(en.find_placeholder_mismatches(&en).is_empty());
+    }
+}
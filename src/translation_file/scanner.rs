@@ -0,0 +1,308 @@
+//! Scans a JSON translation file on its own, for what `serde_json` does not keep.
+//!
+//! `serde_json` silently keeps the last value of a duplicated key, so every definition of each
+//! key is found, including keys that end up the same once nested objects are flattened. It also
+//! resolves the escapes of string values, so their literals are kept to write unchanged values
+//! back as they were.
+
+use std::{collections::HashMap, iter::Peekable, ops::Range, str::CharIndices};
+
+use serde_json::Value;
+
+use super::KEY_SEPARATOR;
+
+/// A key that is defined more than once, with every definition in the order of the file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateKey {
+    pub key: String,
+    pub occurrences: Vec<KeyOccurrence>,
+}
+
+/// One definition of a key. The line and column are 1-based and point at the opening quote.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyOccurrence {
+    pub line: usize,
+    pub column: usize,
+    pub value: Value,
+}
+
+impl DuplicateKey {
+    /// Whether the definitions have different values, so the one that is kept matters.
+    pub fn is_conflicting(&self) -> bool {
+        self.occurrences
+            .windows(2)
+            .any(|pair| pair[0].value != pair[1].value)
+    }
+}
+
+/// What the scan of a file found.
+pub(crate) struct Scan {
+    /// Every key that is defined more than once
+    pub duplicate_keys: Vec<DuplicateKey>,
+    /// The string literal of every string value as it is written, escapes included, by dotted
+    /// key. The last definition of a duplicated key is kept, like `serde_json` does.
+    pub raw_values: HashMap<String, String>,
+}
+
+/// Scan `source`. Scanning stops at the first syntax error, which is reported when the file is
+/// parsed.
+pub(crate) fn scan(source: &str) -> Scan {
+    let mut scanner = Scanner {
+        source,
+        chars: source.char_indices().peekable(),
+        line: 1,
+        column: 1,
+        keys: Vec::new(),
+        index: HashMap::new(),
+    };
+    scanner.skip_whitespace();
+    let _ = scanner.value("");
+
+    let mut raw_values = HashMap::new();
+    let mut duplicate_keys = Vec::new();
+    for (key, definitions) in scanner.keys {
+        if let Some(definition) = definitions.last() {
+            let raw = &source[definition.value.clone()];
+            if raw.starts_with('"') {
+                raw_values.insert(key.clone(), raw.to_string());
+            }
+        }
+        // Values are only parsed for duplicated keys, to compare them
+        if definitions.len() > 1 {
+            let occurrences = definitions
+                .into_iter()
+                .map(|definition| KeyOccurrence {
+                    line: definition.line,
+                    column: definition.column,
+                    value: serde_json::from_str(&source[definition.value]).unwrap_or(Value::Null),
+                })
+                .collect();
+            duplicate_keys.push(DuplicateKey { key, occurrences });
+        }
+    }
+
+    Scan {
+        duplicate_keys,
+        raw_values,
+    }
+}
+
+/// Where a key is defined, and the range of its value in the source.
+struct Definition {
+    line: usize,
+    column: usize,
+    value: Range<usize>,
+}
+
+struct Scanner<'a> {
+    source: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    line: usize,
+    column: usize,
+    /// Every key found with its definitions, in the order of their first definition
+    keys: Vec<(String, Vec<Definition>)>,
+    index: HashMap<String, usize>,
+}
+
+impl Scanner<'_> {
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|(_, c)| *c)
+    }
+
+    fn offset(&mut self) -> usize {
+        self.chars
+            .peek()
+            .map_or(self.source.len(), |(offset, _)| *offset)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let (_, c) = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn expect(&mut self, expected: char) -> Option<()> {
+        (self.bump()? == expected).then_some(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    /// A value whose key is `key`, the dotted key of its parents for nested objects.
+    fn value(&mut self, key: &str) -> Option<()> {
+        match self.peek()? {
+            '{' => self.object(key),
+            '[' => self.array(key),
+            '"' => self.string().map(|_| ()),
+            _ => {
+                // Numbers, booleans and null
+                while self
+                    .peek()
+                    .is_some_and(|c| !c.is_whitespace() && !matches!(c, ',' | '}' | ']'))
+                {
+                    self.bump();
+                }
+                Some(())
+            }
+        }
+    }
+
+    fn object(&mut self, prefix: &str) -> Option<()> {
+        self.expect('{')?;
+        self.skip_whitespace();
+        if self.peek()? == '}' {
+            self.bump();
+            return Some(());
+        }
+
+        loop {
+            let (line, column) = (self.line, self.column);
+            let name = self.string()?;
+            let key = if prefix.is_empty() {
+                name
+            } else {
+                format!("{}{}{}", prefix, KEY_SEPARATOR, name)
+            };
+
+            self.skip_whitespace();
+            self.expect(':')?;
+            self.skip_whitespace();
+
+            let start = self.offset();
+            self.value(&key)?;
+            let end = self.offset();
+            self.add(
+                key,
+                Definition {
+                    line,
+                    column,
+                    value: start..end,
+                },
+            );
+
+            self.skip_whitespace();
+            match self.bump()? {
+                ',' => self.skip_whitespace(),
+                '}' => return Some(()),
+                _ => return None,
+            }
+        }
+    }
+
+    fn array(&mut self, key: &str) -> Option<()> {
+        self.expect('[')?;
+        self.skip_whitespace();
+        if self.peek()? == ']' {
+            self.bump();
+            return Some(());
+        }
+
+        loop {
+            self.value(key)?;
+            self.skip_whitespace();
+            match self.bump()? {
+                ',' => self.skip_whitespace(),
+                ']' => return Some(()),
+                _ => return None,
+            }
+        }
+    }
+
+    /// A string literal, with its escapes resolved.
+    fn string(&mut self) -> Option<String> {
+        self.expect('"')?;
+        let mut value = String::new();
+        loop {
+            match self.bump()? {
+                '"' => return Some(value),
+                '\\' => match self.bump()? {
+                    'n' => value.push('\n'),
+                    't' => value.push('\t'),
+                    'r' => value.push('\r'),
+                    'b' => value.push('\u{8}'),
+                    'f' => value.push('\u{c}'),
+                    'u' => {
+                        let hex = (0..4).map(|_| self.bump()).collect::<Option<String>>()?;
+                        let code = u32::from_str_radix(&hex, 16).ok()?;
+                        value.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                    }
+                    c => value.push(c),
+                },
+                c => value.push(c),
+            }
+        }
+    }
+
+    fn add(&mut self, key: String, definition: Definition) {
+        match self.index.get(&key) {
+            Some(&i) => self.keys[i].1.push(definition),
+            None => {
+                self.index.insert(key.clone(), self.keys.len());
+                self.keys.push((key, vec![definition]));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(duplicate: &DuplicateKey) -> Vec<(usize, usize)> {
+        duplicate
+            .occurrences
+            .iter()
+            .map(|occurrence| (occurrence.line, occurrence.column))
+            .collect()
+    }
+
+    #[test]
+    fn test_colons_are_not_keys() {
+        let source = r#"{
+  "time": "Time: 12:00",
+  "url": "https://example.com",
+  "ns:key": "a",
+  "other:key": "Time: 12:00"
+}"#;
+        assert!(scan(source).duplicate_keys.is_empty());
+    }
+
+    #[test]
+    fn test_same_and_conflicting_values() {
+        let source = r#"{
+  "save": "Save",
+  "cancel": "Cancel",
+  "save": "Save",
+  "cancel": "Abort", "cancel": "Abort"
+}"#;
+        let duplicates = scan(source).duplicate_keys;
+
+        assert_eq!(2, duplicates.len());
+        assert_eq!("save", duplicates[0].key);
+        assert_eq!(vec![(2, 3), (4, 3)], positions(&duplicates[0]));
+        assert!(!duplicates[0].is_conflicting());
+        assert_eq!("cancel", duplicates[1].key);
+        assert_eq!(vec![(3, 3), (5, 3), (5, 22)], positions(&duplicates[1]));
+        assert!(duplicates[1].is_conflicting());
+    }
+
+    #[test]
+    fn test_minified_and_nested() {
+        let source =
+            r#"{"common":{"save":"Save","title":"A"},"other":{"title":"B"},"common.save":"Spara"}"#;
+        let duplicates = scan(source).duplicate_keys;
+
+        assert_eq!(1, duplicates.len());
+        assert_eq!("common.save", duplicates[0].key);
+        assert_eq!(vec![(1, 12), (1, 61)], positions(&duplicates[0]));
+        assert!(duplicates[0].is_conflicting());
+    }
+}
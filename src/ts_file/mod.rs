@@ -0,0 +1,781 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use self::tokenizer::{tokenize, Token, TokenKind};
+
+mod tokenizer;
+
+#[derive(Debug)]
+pub struct TSFile {
+    pub source: String,
+    pub path: PathBuf,
+}
+
+#[derive(Error, Debug)]
+pub enum TSFileError {
+    #[error("could not read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyUsage {
+    /// The key, or a pattern with `*` wildcards if `dynamic` is set
+    pub key: String,
+    /// Whether the key is built at runtime, e.g. `` `product_category.${code}` ``
+    pub dynamic: bool,
+    pub line: usize,
+    pub column: usize,
+    pub file_path: PathBuf,
+    /// Names of the values passed along with the key, like `count` in
+    /// `formatMessage({ id }, { count })`. `None` if they can not be known, for example when
+    /// they are a variable or spread from another object, or the key is not used in a call.
+    #[serde(default)]
+    pub values: Option<Vec<String>>,
+}
+
+/// What to look for when searching for key usages.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UsagePatterns {
+    /// Functions that take the key as `id` in their first argument, e.g. `formatMessage`
+    pub calls: Vec<String>,
+    /// Components that take the key as an `id` prop, e.g. `FormattedMessage`
+    pub components: Vec<String>,
+    /// Object properties (`translationId: "..."`) and JSX props (`titleId="..."`) whose value
+    /// is a key
+    pub props: Vec<String>,
+}
+
+impl Default for UsagePatterns {
+    fn default() -> Self {
+        Self {
+            calls: vec!["formatMessage".to_string()],
+            components: vec!["FormattedMessage".to_string()],
+            props: [
+                "translationId",
+                "translationKey",
+                "transId",
+                "pageTitleId",
+                "titleId",
+            ]
+            .map(String::from)
+            .to_vec(),
+        }
+    }
+}
+
+impl TSFile {
+    pub fn new(path: &Path) -> Result<Self, TSFileError> {
+        let source = std::fs::read_to_string(path).map_err(|source| TSFileError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Ok(Self {
+            source,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Keys passed as `id` to a `<FormattedMessage>` component.
+    pub fn find_formatted_message_usages(&self, patterns: &UsagePatterns) -> Vec<KeyUsage> {
+        self.find_usages(&[], &patterns.components, &[])
+    }
+
+    /// Keys passed as `id` in the first argument of a `formatMessage()` call.
+    pub fn find_format_message_usages(&self, patterns: &UsagePatterns) -> Vec<KeyUsage> {
+        self.find_usages(&patterns.calls, &[], &[])
+    }
+
+    /// Random usage patterns that are used in the codebase, matched both as object properties
+    /// (`translationId: "..."`) and as JSX props (`titleId="..."`).
+    pub fn find_misc_usages(&self, patterns: &UsagePatterns) -> Vec<KeyUsage> {
+        self.find_usages(&[], &[], &patterns.props)
+    }
+
+    /// All of the above, with the file tokenized only once.
+    pub fn find_all_usages(&self, patterns: &UsagePatterns) -> Vec<KeyUsage> {
+        self.find_usages(&patterns.calls, &patterns.components, &patterns.props)
+    }
+
+    fn find_usages(
+        &self,
+        calls: &[String],
+        components: &[String],
+        props: &[String],
+    ) -> Vec<KeyUsage> {
+        let jsx = self
+            .path
+            .extension()
+            .is_some_and(|ext| ext == "tsx" || ext == "jsx");
+        let tokens = tokenize(&self.source, jsx);
+
+        let mut literals = Vec::new();
+        let mut add = |candidates: Vec<_>, values: Option<Vec<String>>| {
+            literals.extend(
+                candidates
+                    .into_iter()
+                    .map(|candidate| (candidate, values.clone())),
+            );
+        };
+        for (i, token) in tokens.iter().enumerate() {
+            match &token.kind {
+                // formatMessage({ id: "..." })
+                TokenKind::Ident(name)
+                    if calls.contains(name)
+                        && tokens.get(i + 1).is_some_and(|t| t.is_punct("(")) =>
+                {
+                    let Some(close) = find_closing(&tokens, i + 1) else {
+                        continue;
+                    };
+                    let arguments = split_top_level(&tokens[i + 2..close], ",");
+                    if let Some(properties) = arguments.first().and_then(|arg| object_literal(arg))
+                    {
+                        let values = match arguments.get(1) {
+                            Some(values) => value_names(values),
+                            None => Some(Vec::new()),
+                        };
+                        add(
+                            properties
+                                .iter()
+                                .filter(|property| property.name == "id")
+                                .flat_map(|property| key_candidates(property.value))
+                                .collect(),
+                            values,
+                        );
+                    }
+                }
+                // <FormattedMessage id="..." values={{ ... }} />
+                TokenKind::JsxOpen(name) if components.contains(name) => {
+                    let attributes = jsx_attributes(&tokens[i + 1..]);
+                    let values = match attributes
+                        .iter()
+                        .find(|attribute| attribute.name == "values")
+                    {
+                        Some(attribute) => value_names(attribute.value),
+                        // `{...props}` may pass the values
+                        None if attributes.iter().any(|attribute| attribute.name.is_empty()) => {
+                            None
+                        }
+                        None => Some(Vec::new()),
+                    };
+                    add(
+                        attributes
+                            .iter()
+                            .filter(|attribute| attribute.name == "id")
+                            .flat_map(|attribute| key_candidates(attribute.value))
+                            .collect(),
+                        values,
+                    );
+                }
+                // translationId: "..."
+                TokenKind::Ident(name)
+                    if props.contains(name)
+                        && tokens.get(i + 1).is_some_and(|t| t.is_punct(":"))
+                        && i > 0
+                        && (tokens[i - 1].is_punct("{") || tokens[i - 1].is_punct(",")) =>
+                {
+                    add(key_candidates(expression_at(&tokens, i + 2)), None);
+                }
+                // titleId="..."
+                TokenKind::JsxAttribute(name)
+                    if props.contains(name)
+                        && tokens.get(i + 1).is_some_and(|t| t.is_punct("=")) =>
+                {
+                    add(key_candidates(attribute_value(&tokens, i + 2)), None);
+                }
+                _ => {}
+            }
+        }
+
+        literals
+            .into_iter()
+            // A pattern without any literal text would match every key
+            .filter(|(candidate, _)| !candidate.key.trim_matches('*').is_empty())
+            .map(|(candidate, values)| KeyUsage {
+                key: candidate.key,
+                dynamic: candidate.dynamic,
+                line: candidate.token.line,
+                column: candidate.token.column,
+                file_path: self.path.to_path_buf(),
+                values,
+            })
+            .collect()
+    }
+}
+
+impl KeyUsage {
+    /// Whether this usage refers to `key`, either directly or through its pattern.
+    pub fn matches(&self, key: &str) -> bool {
+        if !self.dynamic {
+            return self.key == key;
+        }
+
+        let mut parts = self.key.split('*');
+        let Some(mut rest) = key.strip_prefix(parts.next().unwrap_or_default()) else {
+            return false;
+        };
+        let parts = parts.collect::<Vec<_>>();
+        let Some((last, middle)) = parts.split_last() else {
+            return rest.is_empty();
+        };
+        for part in middle {
+            match rest.find(part) {
+                Some(i) => rest = &rest[i + part.len()..],
+                None => return false,
+            }
+        }
+        rest.ends_with(last)
+    }
+}
+
+#[cfg(test)]
+impl KeyUsage {
+    /// A usage of `key` at the start of `file`, for tests.
+    pub fn test(key: &str, file: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            dynamic: false,
+            line: 1,
+            column: 1,
+            file_path: PathBuf::from(file),
+            values: None,
+        }
+    }
+}
+
+/// A key or key pattern found in an expression, with the token it starts at.
+struct Candidate<'a> {
+    key: String,
+    dynamic: bool,
+    token: &'a Token,
+}
+
+/// A named property of an object literal, or a JSX attribute. Spread JSX attributes have an
+/// empty name.
+struct Property<'a> {
+    name: String,
+    value: &'a [Token],
+}
+
+/// How much a token changes the bracket nesting depth.
+fn depth_change(token: &Token) -> i32 {
+    match &token.kind {
+        TokenKind::Punct("(" | "[" | "{") | TokenKind::TemplateHead(_) | TokenKind::JsxOpen(_) => 1,
+        TokenKind::Punct(")" | "]" | "}")
+        | TokenKind::TemplateTail(_)
+        | TokenKind::JsxSelfClose
+        | TokenKind::JsxClose(_) => -1,
+        _ => 0,
+    }
+}
+
+/// Index of the token that closes the bracket opened at `open`.
+fn find_closing(tokens: &[Token], open: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        depth += depth_change(token);
+        if depth == 0 {
+            return Some(i);
+        }
+    }
+    None
+}
+
+/// Split `tokens` on every `separator` that is not nested inside brackets.
+fn split_top_level<'a>(tokens: &'a [Token], separator: &str) -> Vec<&'a [Token]> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        depth += depth_change(token);
+        if depth == 0 && token.is_punct(separator) {
+            parts.push(&tokens[start..i]);
+            start = i + 1;
+        }
+    }
+    parts.push(&tokens[start..]);
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+/// The expression starting at `start`, up to the next top-level `,` or `;` or the end of the
+/// enclosing brackets.
+fn expression_at(tokens: &[Token], start: usize) -> &[Token] {
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(start) {
+        if depth == 0 && (token.is_punct(",") || token.is_punct(";")) {
+            return &tokens[start..i];
+        }
+        depth += depth_change(token);
+        if depth < 0 {
+            return &tokens[start..i];
+        }
+    }
+    tokens.get(start..).unwrap_or_default()
+}
+
+/// The value of a JSX attribute starting at `start`, either a string or a `{...}` expression.
+fn attribute_value(tokens: &[Token], start: usize) -> &[Token] {
+    match tokens.get(start) {
+        Some(token) if token.is_punct("{") => match find_closing(tokens, start) {
+            Some(close) => &tokens[start + 1..close],
+            None => &[],
+        },
+        Some(_) => &tokens[start..start + 1],
+        None => &[],
+    }
+}
+
+/// The properties of `tokens` if they form an object literal.
+fn object_literal(tokens: &[Token]) -> Option<Vec<Property<'_>>> {
+    if !tokens.first()?.is_punct("{") || find_closing(tokens, 0)? != tokens.len() - 1 {
+        return None;
+    }
+
+    let properties = split_top_level(&tokens[1..tokens.len() - 1], ",")
+        .into_iter()
+        .filter_map(|property| match property {
+            [Token {
+                kind: TokenKind::Ident(name) | TokenKind::String(name),
+                ..
+            }, colon, value @ ..]
+                if colon.is_punct(":") =>
+            {
+                Some(Property {
+                    name: name.clone(),
+                    value,
+                })
+            }
+            // Shorthand property
+            [Token {
+                kind: TokenKind::Ident(name),
+                ..
+            }] => Some(Property {
+                name: name.clone(),
+                value: property,
+            }),
+            _ => None,
+        })
+        .collect();
+
+    Some(properties)
+}
+
+/// The attributes of the JSX element whose opening tag starts right before `tokens`.
+fn jsx_attributes(tokens: &[Token]) -> Vec<Property<'_>> {
+    let mut attributes = Vec::new();
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        if depth == 0 {
+            match &token.kind {
+                TokenKind::JsxOpenEnd | TokenKind::JsxSelfClose => break,
+                // {...props}
+                TokenKind::Punct("{") if i == 0 || !tokens[i - 1].is_punct("=") => {
+                    attributes.push(Property {
+                        name: String::new(),
+                        value: &[],
+                    });
+                }
+                TokenKind::JsxAttribute(name) => {
+                    let value = if tokens.get(i + 1).is_some_and(|t| t.is_punct("=")) {
+                        attribute_value(tokens, i + 2)
+                    } else {
+                        &[]
+                    };
+                    attributes.push(Property {
+                        name: name.clone(),
+                        value,
+                    });
+                }
+                _ => {}
+            }
+        }
+        depth += depth_change(token);
+    }
+    attributes
+}
+
+/// The names of the values in an object literal, `None` if it is something else or has spread
+/// or computed properties.
+fn value_names(tokens: &[Token]) -> Option<Vec<String>> {
+    let properties = object_literal(tokens)?;
+    let count = split_top_level(&tokens[1..tokens.len() - 1], ",").len();
+    (properties.len() == count).then(|| {
+        properties
+            .into_iter()
+            .map(|property| property.name)
+            .collect()
+    })
+}
+
+/// The keys an expression can evaluate to, following ternaries and `||`/`??` fallbacks.
+///
+/// Template literals and string concatenations with runtime parts become patterns where every
+/// runtime part is a `*` wildcard. Expressions without any literal part are skipped.
+fn key_candidates(tokens: &[Token]) -> Vec<Candidate<'_>> {
+    let mut tokens = tokens;
+
+    // Strip `"key" as const` and `"key" satisfies MessageId`
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        depth += depth_change(token);
+        if depth == 0
+            && i > 0
+            && matches!(&token.kind, TokenKind::Ident(word) if word == "as" || word == "satisfies")
+        {
+            tokens = &tokens[..i];
+            break;
+        }
+    }
+    let tokens = strip_parentheses(tokens);
+
+    // condition ? a : b
+    if let Some((question, colon)) = find_ternary(tokens) {
+        let mut candidates = key_candidates(&tokens[question + 1..colon]);
+        candidates.extend(key_candidates(&tokens[colon + 1..]));
+        return candidates;
+    }
+
+    // a || b, a ?? b
+    for operator in ["||", "??"] {
+        let operands = split_top_level(tokens, operator);
+        if operands.len() > 1 {
+            return operands.into_iter().flat_map(key_candidates).collect();
+        }
+    }
+
+    // "prefix." + code
+    let Some(first) = tokens.first() else {
+        return Vec::new();
+    };
+    let parts = split_top_level(tokens, "+")
+        .into_iter()
+        .map(|operand| literal_pattern(strip_parentheses(operand)))
+        .collect::<Vec<_>>();
+    if parts.iter().all(|part| part.is_none()) {
+        return Vec::new();
+    }
+
+    let dynamic = parts
+        .iter()
+        .any(|part| part.as_ref().is_none_or(|(_, dynamic)| *dynamic));
+    let key = parts
+        .into_iter()
+        .map(|part| part.map_or("*".to_string(), |(pattern, _)| pattern))
+        .collect::<String>();
+    vec![Candidate {
+        key: collapse_wildcards(&key),
+        dynamic,
+        token: first,
+    }]
+}
+
+/// The pattern of a single string or template literal, and whether it has runtime parts.
+fn literal_pattern(tokens: &[Token]) -> Option<(String, bool)> {
+    match tokens {
+        [Token {
+            kind: TokenKind::String(value) | TokenKind::Template(value),
+            ..
+        }] => Some((value.clone(), false)),
+        [Token {
+            kind: TokenKind::TemplateHead(head),
+            ..
+        }, .., Token {
+            kind: TokenKind::TemplateTail(tail),
+            ..
+        }] if find_closing(tokens, 0) == Some(tokens.len() - 1) => {
+            let mut pattern = format!("{}*", head);
+            let mut depth = 0;
+            for token in tokens {
+                depth += depth_change(token);
+                if let (1, TokenKind::TemplateMiddle(middle)) = (depth, &token.kind) {
+                    pattern.push_str(middle);
+                    pattern.push('*');
+                }
+            }
+            pattern.push_str(tail);
+            Some((pattern, true))
+        }
+        _ => None,
+    }
+}
+
+fn collapse_wildcards(pattern: &str) -> String {
+    let mut collapsed = String::with_capacity(pattern.len());
+    for c in pattern.chars() {
+        if c != '*' || !collapsed.ends_with('*') {
+            collapsed.push(c);
+        }
+    }
+    collapsed
+}
+
+fn strip_parentheses(mut tokens: &[Token]) -> &[Token] {
+    while tokens.first().is_some_and(|t| t.is_punct("("))
+        && find_closing(tokens, 0) == Some(tokens.len() - 1)
+    {
+        tokens = &tokens[1..tokens.len() - 1];
+    }
+    tokens
+}
+
+/// Positions of the top-level `?` and its matching `:`.
+fn find_ternary(tokens: &[Token]) -> Option<(usize, usize)> {
+    let mut depth = 0;
+    let mut question = None;
+    let mut nested = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        depth += depth_change(token);
+        if depth != 0 {
+            continue;
+        }
+        if token.is_punct("?") {
+            if question.is_none() {
+                question = Some(i);
+            } else {
+                nested += 1;
+            }
+        } else if token.is_punct(":") && question.is_some() {
+            if nested == 0 {
+                return question.map(|question| (question, i));
+            }
+            nested -= 1;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usages_in(source: &str) -> Vec<(String, usize, usize)> {
+        dynamic_usages_in(source)
+            .into_iter()
+            .map(|(key, _, line, column)| (key, line, column))
+            .collect()
+    }
+
+    fn dynamic_usages_in(source: &str) -> Vec<(String, bool, usize, usize)> {
+        let ts_file = TSFile {
+            source: source.to_string(),
+            path: PathBuf::from("test.tsx"),
+        };
+        let patterns = UsagePatterns::default();
+        let mut usages = ts_file.find_format_message_usages(&patterns);
+        usages.extend(ts_file.find_formatted_message_usages(&patterns));
+        usages.extend(ts_file.find_misc_usages(&patterns));
+        usages
+            .into_iter()
+            .map(|usage| (usage.key, usage.dynamic, usage.line, usage.column))
+            .collect()
+    }
+
+    #[test]
+    fn test_find_format_message_usages() {
+        let path = Path::new("test_files/component.tsx");
+        let ts_file = TSFile::new(path).unwrap();
+        let actual = ts_file.find_format_message_usages(&UsagePatterns::default());
+        let expected = vec![KeyUsage {
+            key: "name".to_string(),
+            dynamic: false,
+            line: 20,
+            column: 35,
+            file_path: path.to_path_buf(),
+            values: Some(Vec::new()),
+        }];
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_find_formatted_message_usages() {
+        let path = Path::new("test_files/component.tsx");
+        let ts_file = TSFile::new(path).unwrap();
+        let actual = ts_file.find_formatted_message_usages(&UsagePatterns::default());
+        let expected = vec![
+            KeyUsage {
+                key: "name".to_string(),
+                dynamic: false,
+                line: 22,
+                column: 28,
+                file_path: path.to_path_buf(),
+                values: Some(Vec::new()),
+            },
+            KeyUsage {
+                key: "name".to_string(),
+                dynamic: false,
+                line: 23,
+                column: 29,
+                file_path: path.to_path_buf(),
+                values: Some(Vec::new()),
+            },
+        ];
+        assert_eq!(expected, actual);
+
+        let path = Path::new("test_files/select-component.tsx");
+        let ts_file = TSFile::new(path).unwrap();
+        let actual = ts_file
+            .find_formatted_message_usages(&UsagePatterns::default())
+            .into_iter()
+            .map(|usage| (usage.key, usage.line))
+            .collect::<Vec<_>>();
+        let expected = vec![
+            ("sustainability_admin.add_product".to_string(), 122),
+            ("sustainability_admin.edit_product".to_string(), 123),
+            ("common.cancel".to_string(), 208),
+            ("common.save".to_string(), 212),
+        ];
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_simon_case() {
+        let path = Path::new("test_files/simon-case.tsx");
+        let ts_file = TSFile::new(path).unwrap();
+
+        let actual = ts_file.find_format_message_usages(&UsagePatterns::default());
+        let expected = vec![
+            KeyUsage {
+                key: "common.product_category".to_string(),
+                dynamic: false,
+                line: 5,
+                column: 40,
+                file_path: "test_files/simon-case.tsx".into(),
+                values: Some(Vec::new()),
+            },
+            KeyUsage {
+                key: "product_category.*".to_string(),
+                dynamic: true,
+                line: 7,
+                column: 51,
+                file_path: "test_files/simon-case.tsx".into(),
+                values: Some(Vec::new()),
+            },
+            KeyUsage {
+                key: "common.project_description".to_string(),
+                dynamic: false,
+                line: 11,
+                column: 40,
+                file_path: "test_files/simon-case.tsx".into(),
+                values: Some(Vec::new()),
+            },
+        ];
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_misc_usages() {
+        let actual = usages_in(
+            r#"const a = { translationId: "some_id" };
+<Page pageTitleId="page_title" titleId={"title"} />"#,
+        );
+        let expected = vec![
+            ("some_id".to_string(), 1, 28),
+            ("page_title".to_string(), 2, 19),
+            ("title".to_string(), 2, 41),
+        ];
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_multi_line_props_and_spread() {
+        let actual = usages_in(
+            r#"<FormattedMessage
+  {...rest}
+  // id="commented_out"
+  values={{ a: <b>x</b> }}
+  id={
+    a ? "first" : b ? "second" : "third"
+  }
+/>"#,
+        );
+        let expected = vec![
+            ("first".to_string(), 6, 9),
+            ("second".to_string(), 6, 23),
+            ("third".to_string(), 6, 34),
+        ];
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_nested_calls_and_comments() {
+        let actual = usages_in(
+            r#"/* intl.formatMessage({ id: "in_comment" }) */
+const label = `${intl.formatMessage({ description: "x", id: 'nested' })}:`;
+const other = intl.formatMessage({ id: props.id ?? "fallback" }, { count });"#,
+        );
+        let expected = vec![
+            ("nested".to_string(), 2, 61),
+            ("fallback".to_string(), 3, 52),
+        ];
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_values() {
+        let ts_file = TSFile {
+            source: r#"intl.formatMessage({ id: "a" }, { count: n, name });
+intl.formatMessage({ id: "b" }, values);
+intl.formatMessage({ id: "c" }, { ...rest, count });
+<FormattedMessage id={open ? "d" : "e"} values={{ b: (chunks) => <b>{chunks}</b> }} />;
+<FormattedMessage {...props} id="f" />;
+<Page titleId="g" />"#
+                .to_string(),
+            path: PathBuf::from("test.tsx"),
+        };
+        let actual = ts_file
+            .find_all_usages(&UsagePatterns::default())
+            .into_iter()
+            .map(|usage| (usage.key, usage.values))
+            .collect::<Vec<_>>();
+
+        let names = |names: &[&str]| Some(names.iter().map(|name| name.to_string()).collect());
+        let expected = vec![
+            ("a".to_string(), names(&["count", "name"])),
+            ("b".to_string(), None),
+            ("c".to_string(), None),
+            ("d".to_string(), names(&["b"])),
+            ("e".to_string(), names(&["b"])),
+            ("f".to_string(), None),
+            ("g".to_string(), None),
+        ];
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_dynamic_keys() {
+        let actual = dynamic_usages_in(
+            r#"intl.formatMessage({ id: `status.${a}.label` });
+intl.formatMessage({ id: "errors." + code });
+intl.formatMessage({ id: `${a}` });
+intl.formatMessage({ id: `plain` });"#,
+        );
+        let expected = vec![
+            ("status.*.label".to_string(), true, 1, 26),
+            ("errors.*".to_string(), true, 2, 26),
+            ("plain".to_string(), false, 4, 26),
+        ];
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_key_usage_matches() {
+        let usage = |key: &str, dynamic: bool| KeyUsage {
+            dynamic,
+            ..KeyUsage::test(key, "test.tsx")
+        };
+
+        assert!(usage("common.save", false).matches("common.save"));
+        assert!(!usage("common.*", false).matches("common.save"));
+        assert!(usage("common.*", true).matches("common.save"));
+        assert!(!usage("common.*", true).matches("other.save"));
+        assert!(usage("status.*.label", true).matches("status.open.label"));
+        assert!(!usage("status.*.label", true).matches("status.open.title"));
+    }
+}
@@ -0,0 +1,640 @@
+//! A small tokenizer for TypeScript and TSX.
+//!
+//! It does not try to understand the full language, only enough to reliably tell apart string
+//! literals, template literals, comments, regular expressions and JSX, so that the extractor can
+//! work on tokens instead of raw lines.
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TokenKind {
+    Ident(String),
+    /// String literal with its escapes resolved
+    String(String),
+    /// Template literal without any substitutions
+    Template(String),
+    /// Start of a template literal, up to the first `${`
+    TemplateHead(String),
+    /// Template text between two substitutions
+    TemplateMiddle(String),
+    /// Template text after the last substitution, up to the closing backtick
+    TemplateTail(String),
+    Number,
+    Regex,
+    Punct(&'static str),
+    /// `<Name` opening a JSX element, the name is empty for fragments
+    JsxOpen(String),
+    /// `>` ending the opening tag of a JSX element that has children
+    JsxOpenEnd,
+    /// `/>` ending a self-closing JSX element
+    JsxSelfClose,
+    /// `</Name>` closing a JSX element, the name is empty for fragments
+    JsxClose(String),
+    JsxAttribute(String),
+    JsxText,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Token {
+    pub kind: TokenKind,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Token {
+    pub fn is_punct(&self, punct: &str) -> bool {
+        matches!(&self.kind, TokenKind::Punct(p) if *p == punct)
+    }
+}
+
+// Longest first, so that the first match is the right one
+static PUNCTUATORS: [&str; 50] = [
+    ">>>=", "...", "===", "!==", "**=", "<<=", ">>=", ">>>", "&&=", "||=", "??=", "=>", "==", "!=",
+    "<=", ">=", "&&", "||", "??", "?.", "++", "--", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=",
+    "**", "<<", ">>", "{", "}", "(", ")", "[", "]", ";", ",", "<", ">", "+", "-", "*", "/", "%",
+    "=", "?",
+];
+
+static SINGLE_PUNCTUATORS: [&str; 9] = [":", "!", "~", "&", "|", "^", ".", "@", "#"];
+
+// Keywords after which an expression (and therefore a regex or JSX) can start
+static EXPRESSION_KEYWORDS: [&str; 14] = [
+    "return",
+    "typeof",
+    "instanceof",
+    "in",
+    "of",
+    "new",
+    "delete",
+    "void",
+    "throw",
+    "case",
+    "do",
+    "else",
+    "yield",
+    "await",
+];
+
+#[derive(Debug, Clone, Copy)]
+enum Mode {
+    Code { braces: usize },
+    Template,
+    JsxTag,
+    JsxChildren,
+}
+
+struct Tokenizer {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    column: usize,
+    jsx: bool,
+    modes: Vec<Mode>,
+    tokens: Vec<Token>,
+}
+
+/// Split `source` into tokens. JSX is only recognized if `jsx` is set, since `<` is ambiguous
+/// with type assertions in plain `.ts` files.
+pub(crate) fn tokenize(source: &str, jsx: bool) -> Vec<Token> {
+    let mut tokenizer = Tokenizer {
+        chars: source.chars().collect(),
+        pos: 0,
+        line: 1,
+        column: 1,
+        jsx,
+        modes: vec![Mode::Code { braces: 0 }],
+        tokens: Vec::new(),
+    };
+    tokenizer.run();
+    tokenizer.tokens
+}
+
+impl Tokenizer {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars()
+            .enumerate()
+            .all(|(i, c)| self.peek_at(i) == Some(c))
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn push(&mut self, kind: TokenKind, line: usize, column: usize) {
+        self.tokens.push(Token { kind, line, column });
+    }
+
+    fn run(&mut self) {
+        while self.pos < self.chars.len() {
+            match *self.modes.last().unwrap_or(&Mode::Code { braces: 0 }) {
+                Mode::Code { braces } => self.code(braces),
+                Mode::Template => self.template(false, self.line, self.column),
+                Mode::JsxTag => self.jsx_tag(),
+                Mode::JsxChildren => self.jsx_children(),
+            }
+        }
+    }
+
+    fn set_braces(&mut self, braces: usize) {
+        if let Some(Mode::Code { braces: b }) = self.modes.last_mut() {
+            *b = braces;
+        }
+    }
+
+    /// Skip whitespace and comments, returns true if anything was skipped.
+    fn skip_trivia(&mut self) -> bool {
+        let start = self.pos;
+        loop {
+            match self.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                }
+                Some('/') if self.peek_at(1) == Some('/') => {
+                    while self.peek().is_some_and(|c| c != '\n') {
+                        self.bump();
+                    }
+                }
+                Some('/') if self.peek_at(1) == Some('*') => {
+                    self.bump();
+                    self.bump();
+                    while self.peek().is_some() && !self.starts_with("*/") {
+                        self.bump();
+                    }
+                    self.bump();
+                    self.bump();
+                }
+                _ => break,
+            }
+        }
+        self.pos != start
+    }
+
+    fn expression_allowed(&self) -> bool {
+        match self.tokens.last().map(|token| &token.kind) {
+            None => true,
+            Some(TokenKind::Punct(p)) => !matches!(*p, ")" | "]" | "}" | "++" | "--"),
+            Some(TokenKind::Ident(word)) => EXPRESSION_KEYWORDS.contains(&word.as_str()),
+            Some(TokenKind::TemplateHead(_) | TokenKind::TemplateMiddle(_)) => true,
+            Some(_) => false,
+        }
+    }
+
+    fn code(&mut self, braces: usize) {
+        if self.skip_trivia() {
+            return;
+        }
+        let (line, column) = (self.line, self.column);
+        let Some(c) = self.peek() else {
+            return;
+        };
+
+        match c {
+            '{' => {
+                self.bump();
+                self.set_braces(braces + 1);
+                self.push(TokenKind::Punct("{"), line, column);
+            }
+            '}' => {
+                self.bump();
+                if braces == 0 && self.modes.len() > 1 {
+                    self.modes.pop();
+                    match self.modes.last() {
+                        // The closing brace of a substitution is part of the template text
+                        Some(Mode::Template) => self.template(false, line, column),
+                        _ => self.push(TokenKind::Punct("}"), line, column),
+                    }
+                } else {
+                    self.set_braces(braces.saturating_sub(1));
+                    self.push(TokenKind::Punct("}"), line, column);
+                }
+            }
+            '`' => {
+                self.bump();
+                self.template(true, line, column);
+            }
+            '"' | '\'' => {
+                let value = self.string(c);
+                self.push(TokenKind::String(value), line, column);
+            }
+            c if c.is_ascii_digit()
+                || (c == '.' && self.peek_at(1).is_some_and(|c| c.is_ascii_digit())) =>
+            {
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_')
+                {
+                    self.bump();
+                }
+                self.push(TokenKind::Number, line, column);
+            }
+            c if is_ident_start(c) => {
+                let ident = self.ident();
+                self.push(TokenKind::Ident(ident), line, column);
+            }
+            '/' if self.expression_allowed() => {
+                self.regex();
+                self.push(TokenKind::Regex, line, column);
+            }
+            '<' if self.jsx && self.expression_allowed() && self.jsx_ahead() => {
+                self.bump();
+                let name = self.jsx_name();
+                self.modes.push(Mode::JsxTag);
+                self.push(TokenKind::JsxOpen(name), line, column);
+            }
+            _ => {
+                let punct = PUNCTUATORS
+                    .iter()
+                    .chain(SINGLE_PUNCTUATORS.iter())
+                    .find(|p| self.starts_with(p))
+                    // `?.5` is a ternary followed by a number
+                    .filter(|p| {
+                        **p != "?." || !self.peek_at(2).is_some_and(|c| c.is_ascii_digit())
+                    });
+                match punct {
+                    Some(punct) => {
+                        for _ in 0..punct.len() {
+                            self.bump();
+                        }
+                        self.push(TokenKind::Punct(punct), line, column);
+                    }
+                    // Unknown character, e.g. a stray backslash
+                    None => {
+                        self.bump();
+                    }
+                }
+            }
+        }
+    }
+
+    fn ident(&mut self) -> String {
+        let mut ident = String::new();
+        while let Some(c) = self.peek().filter(|c| is_ident_part(*c)) {
+            ident.push(c);
+            self.bump();
+        }
+        ident
+    }
+
+    fn escape(&mut self, value: &mut String) {
+        match self.bump() {
+            Some('n') => value.push('\n'),
+            Some('t') => value.push('\t'),
+            Some('r') => value.push('\r'),
+            Some('b') => value.push('\u{8}'),
+            Some('f') => value.push('\u{c}'),
+            Some('v') => value.push('\u{b}'),
+            Some('0') => value.push('\0'),
+            Some('u') => {
+                let mut hex = String::new();
+                if self.peek() == Some('{') {
+                    self.bump();
+                    while let Some(c) = self.peek().filter(|c| *c != '}') {
+                        hex.push(c);
+                        self.bump();
+                    }
+                    self.bump();
+                } else {
+                    for _ in 0..4 {
+                        if let Some(c) = self.bump() {
+                            hex.push(c);
+                        }
+                    }
+                }
+                if let Some(c) = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    value.push(c);
+                }
+            }
+            Some('x') => {
+                let hex = [self.bump(), self.bump()]
+                    .into_iter()
+                    .flatten()
+                    .collect::<String>();
+                if let Some(c) = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    value.push(c);
+                }
+            }
+            // Line continuation
+            Some('\n') => {}
+            Some(c) => value.push(c),
+            None => {}
+        }
+    }
+
+    fn string(&mut self, quote: char) -> String {
+        self.bump();
+        let mut value = String::new();
+        while let Some(c) = self.peek() {
+            match c {
+                c if c == quote => {
+                    self.bump();
+                    break;
+                }
+                // Unterminated string, stop at the end of the line
+                '\n' => break,
+                '\\' => {
+                    self.bump();
+                    self.escape(&mut value);
+                }
+                c => {
+                    value.push(c);
+                    self.bump();
+                }
+            }
+        }
+        value
+    }
+
+    /// Scan template text, either right after the opening backtick (`head`) or after the closing
+    /// brace of a substitution.
+    fn template(&mut self, head: bool, line: usize, column: usize) {
+        let mut text = String::new();
+        loop {
+            match self.peek() {
+                None | Some('`') => {
+                    self.bump();
+                    if head {
+                        self.push(TokenKind::Template(text), line, column);
+                    } else {
+                        self.modes.pop();
+                        self.push(TokenKind::TemplateTail(text), line, column);
+                    }
+                    return;
+                }
+                Some('\\') => {
+                    self.bump();
+                    self.escape(&mut text);
+                }
+                Some('$') if self.peek_at(1) == Some('{') => {
+                    self.bump();
+                    self.bump();
+                    if head {
+                        self.modes.push(Mode::Template);
+                        self.push(TokenKind::TemplateHead(text), line, column);
+                    } else {
+                        self.push(TokenKind::TemplateMiddle(text), line, column);
+                    }
+                    self.modes.push(Mode::Code { braces: 0 });
+                    return;
+                }
+                Some(c) => {
+                    text.push(c);
+                    self.bump();
+                }
+            }
+        }
+    }
+
+    fn regex(&mut self) {
+        self.bump();
+        let mut in_class = false;
+        while let Some(c) = self.peek() {
+            match c {
+                '\n' => break,
+                '\\' => {
+                    self.bump();
+                    self.bump();
+                    continue;
+                }
+                '[' => in_class = true,
+                ']' => in_class = false,
+                '/' if !in_class => {
+                    self.bump();
+                    break;
+                }
+                _ => {}
+            }
+            self.bump();
+        }
+        // Flags
+        while self.peek().is_some_and(is_ident_part) {
+            self.bump();
+        }
+    }
+
+    /// Whether the `<` at the current position starts a JSX element rather than a generic
+    /// parameter list like `<T,>` or `<T extends U>`.
+    fn jsx_ahead(&self) -> bool {
+        let mut i = 1;
+        while self.peek_at(i).is_some_and(char::is_whitespace) {
+            i += 1;
+        }
+        match self.peek_at(i) {
+            Some('>') => true,
+            Some(c) if is_ident_start(c) => {
+                while self.peek_at(i).is_some_and(is_jsx_name_part) {
+                    i += 1;
+                }
+                let rest = self.chars[(self.pos + i).min(self.chars.len())..]
+                    .iter()
+                    .skip_while(|c| c.is_whitespace())
+                    .take(8)
+                    .collect::<String>();
+                !(rest.starts_with(',') || rest.starts_with("extends "))
+            }
+            _ => false,
+        }
+    }
+
+    fn jsx_name(&mut self) -> String {
+        self.skip_trivia();
+        let mut name = String::new();
+        while let Some(c) = self.peek().filter(|c| is_jsx_name_part(*c)) {
+            name.push(c);
+            self.bump();
+        }
+        name
+    }
+
+    fn jsx_tag(&mut self) {
+        if self.skip_trivia() {
+            return;
+        }
+        let (line, column) = (self.line, self.column);
+        let Some(c) = self.peek() else {
+            return;
+        };
+
+        match c {
+            '/' if self.peek_at(1) == Some('>') => {
+                self.bump();
+                self.bump();
+                self.modes.pop();
+                self.push(TokenKind::JsxSelfClose, line, column);
+            }
+            '>' => {
+                self.bump();
+                if let Some(mode) = self.modes.last_mut() {
+                    *mode = Mode::JsxChildren;
+                }
+                self.push(TokenKind::JsxOpenEnd, line, column);
+            }
+            '{' => {
+                self.bump();
+                self.modes.push(Mode::Code { braces: 0 });
+                self.push(TokenKind::Punct("{"), line, column);
+            }
+            '=' => {
+                self.bump();
+                self.push(TokenKind::Punct("="), line, column);
+            }
+            '"' | '\'' => {
+                // JSX attribute strings have no escapes
+                self.bump();
+                let mut value = String::new();
+                while let Some(ch) = self.bump().filter(|ch| *ch != c) {
+                    value.push(ch);
+                }
+                self.push(TokenKind::String(value), line, column);
+            }
+            c if is_ident_start(c) => {
+                let name = self.jsx_name();
+                self.push(TokenKind::JsxAttribute(name), line, column);
+            }
+            // Anything else, e.g. type arguments on a component, is skipped
+            _ => {
+                self.bump();
+            }
+        }
+    }
+
+    fn jsx_children(&mut self) {
+        let (line, column) = (self.line, self.column);
+        match self.peek() {
+            Some('<') => {
+                self.bump();
+                self.skip_trivia();
+                if self.peek() == Some('/') {
+                    self.bump();
+                    let name = self.jsx_name();
+                    while self.peek().is_some_and(|c| c != '>') {
+                        self.bump();
+                    }
+                    self.bump();
+                    self.modes.pop();
+                    self.push(TokenKind::JsxClose(name), line, column);
+                } else {
+                    let name = self.jsx_name();
+                    self.modes.push(Mode::JsxTag);
+                    self.push(TokenKind::JsxOpen(name), line, column);
+                }
+            }
+            Some('{') => {
+                self.bump();
+                self.modes.push(Mode::Code { braces: 0 });
+                self.push(TokenKind::Punct("{"), line, column);
+            }
+            Some(_) => {
+                let mut only_whitespace = true;
+                while let Some(c) = self.peek().filter(|c| *c != '<' && *c != '{') {
+                    only_whitespace &= c.is_whitespace();
+                    self.bump();
+                }
+                if !only_whitespace {
+                    self.push(TokenKind::JsxText, line, column);
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == '$'
+}
+
+fn is_ident_part(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+fn is_jsx_name_part(c: char) -> bool {
+    is_ident_part(c) || c == '-' || c == '.' || c == ':'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<TokenKind> {
+        tokenize(source, true)
+            .into_iter()
+            .map(|token| token.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_comments_and_strings() {
+        let actual = kinds("// \"not a string\"\nx = 'it\\'s' /* `nope` */;");
+        let expected = vec![
+            TokenKind::Ident("x".to_string()),
+            TokenKind::Punct("="),
+            TokenKind::String("it's".to_string()),
+            TokenKind::Punct(";"),
+        ];
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_template_with_substitution() {
+        let actual = kinds("`a.${b ? `c` : d}.e`");
+        let expected = vec![
+            TokenKind::TemplateHead("a.".to_string()),
+            TokenKind::Ident("b".to_string()),
+            TokenKind::Punct("?"),
+            TokenKind::Template("c".to_string()),
+            TokenKind::Punct(":"),
+            TokenKind::Ident("d".to_string()),
+            TokenKind::TemplateTail(".e".to_string()),
+        ];
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_jsx_text_is_not_code() {
+        let actual = kinds("<p title=\"x\">Don't {a}</p>");
+        let expected = vec![
+            TokenKind::JsxOpen("p".to_string()),
+            TokenKind::JsxAttribute("title".to_string()),
+            TokenKind::Punct("="),
+            TokenKind::String("x".to_string()),
+            TokenKind::JsxOpenEnd,
+            TokenKind::JsxText,
+            TokenKind::Punct("{"),
+            TokenKind::Ident("a".to_string()),
+            TokenKind::Punct("}"),
+            TokenKind::JsxClose("p".to_string()),
+        ];
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_division_and_regex() {
+        let actual = kinds("a / b; x = /[/]\"/g;");
+        assert!(actual.contains(&TokenKind::Punct("/")));
+        assert!(actual.contains(&TokenKind::Regex));
+        assert!(!actual
+            .iter()
+            .any(|kind| matches!(kind, TokenKind::String(_))));
+    }
+
+    #[test]
+    fn test_generic_comparison_is_not_jsx() {
+        let actual = kinds("const a = useState<string>(\"x\"); if (a < b) {}");
+        assert!(!actual
+            .iter()
+            .any(|kind| matches!(kind, TokenKind::JsxOpen(_))));
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use crate::ts_file::KeyUsage;
+
+/// All key usages found in the source files, indexed by key.
+///
+/// Static keys are looked up directly. Dynamic keys, like `` `product_category.${code}` ``, are
+/// patterns that have to be matched against the key, but there are usually only a few of them.
+#[derive(Debug, Clone, Default)]
+pub struct UsageIndex {
+    /// Every usage, in the order they were found
+    usages: Vec<KeyUsage>,
+    /// Indices of the usages of each static key
+    keys: HashMap<String, Vec<usize>>,
+    /// Indices of the usages of dynamic keys
+    patterns: Vec<usize>,
+}
+
+impl UsageIndex {
+    pub fn new(usages: impl IntoIterator<Item = KeyUsage>) -> Self {
+        let mut index = Self::default();
+        for usage in usages {
+            index.insert(usage);
+        }
+        index
+    }
+
+    pub fn insert(&mut self, usage: KeyUsage) {
+        let i = self.usages.len();
+        if usage.dynamic {
+            self.patterns.push(i);
+        } else {
+            self.keys.entry(usage.key.clone()).or_default().push(i);
+        }
+        self.usages.push(usage);
+    }
+
+    /// Every usage of `key`, both direct ones and patterns that match it.
+    pub fn usages_of<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a KeyUsage> {
+        let direct = self.keys.get(key).into_iter().flatten();
+        let patterns = self
+            .patterns
+            .iter()
+            .filter(move |&&i| self.usages[i].matches(key));
+        direct.chain(patterns).map(|&i| &self.usages[i])
+    }
+
+    pub fn is_used(&self, key: &str) -> bool {
+        self.keys.contains_key(key) || self.patterns().any(|usage| usage.matches(key))
+    }
+
+    /// Every usage, in the order they were found.
+    pub fn iter(&self) -> impl Iterator<Item = &KeyUsage> {
+        self.usages.iter()
+    }
+
+    /// The keys that are used directly, without patterns.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.keys.keys().map(String::as_str)
+    }
+
+    /// The usages of dynamic keys.
+    pub fn patterns(&self) -> impl Iterator<Item = &KeyUsage> {
+        self.patterns.iter().map(|&i| &self.usages[i])
+    }
+
+    pub fn len(&self) -> usize {
+        self.usages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.usages.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usages_of() {
+        let usage = |key: &str, dynamic, line| KeyUsage {
+            dynamic,
+            line,
+            ..KeyUsage::test(key, "Button.tsx")
+        };
+        let index = UsageIndex::new([
+            usage("common.save", false, 1),
+            usage("common.*", true, 2),
+            usage("common.save", false, 3),
+        ]);
+
+        let lines = |key| {
+            index
+                .usages_of(key)
+                .map(|usage| usage.line)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec![1, 3, 2], lines("common.save"));
+        assert_eq!(vec![2], lines("common.cancel"));
+        assert!(!index.is_used("other.save"));
+        assert_eq!(3, index.len());
+    }
+}
@@ -0,0 +1,126 @@
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+
+use notify::{EventKind, RecursiveMode, Watcher};
+use thiserror::Error;
+use walkdir::WalkDir;
+
+/// Changes that happen within this time of each other are handled together, editors and
+/// formatters often write a file several times when saving.
+static DEBOUNCE: Duration = Duration::from_millis(200);
+
+#[derive(Error, Debug)]
+pub enum WatchError {
+    #[error("could not watch {path}: {source}")]
+    Watch {
+        path: PathBuf,
+        source: notify::Error,
+    },
+    #[error("file watcher failed: {0}")]
+    Notify(#[from] notify::Error),
+}
+
+/// Watch `dirs` and their subdirectories, except those that `is_excluded`, and `files` through
+/// their parent directory since editors often replace files instead of writing to them. Every
+/// directory is watched on its own, so that excluded directories such as `node_modules` do not
+/// use up the watches the system allows, and directories created while watching are watched too.
+/// `on_change` is called with the absolute paths of the files that changed, until the watcher
+/// fails.
+pub fn watch(
+    dirs: &[PathBuf],
+    files: &[PathBuf],
+    is_excluded: impl Fn(&Path) -> bool,
+    mut on_change: impl FnMut(Vec<PathBuf>),
+) -> Result<(), WatchError> {
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = Watches {
+        watcher: notify::recommended_watcher(sender)?,
+        watched: BTreeSet::new(),
+    };
+
+    for dir in dirs {
+        watcher.add_tree(dir, &is_excluded)?;
+    }
+    for file in files {
+        let parent = file
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty());
+        watcher.add(parent.unwrap_or(Path::new(".")))?;
+    }
+
+    while let Ok(event) = receiver.recv() {
+        let mut changed = BTreeSet::new();
+        let mut add = |event: notify::Result<notify::Event>| -> Result<(), WatchError> {
+            let event = event?;
+            if matches!(event.kind, EventKind::Access(_)) {
+                return Ok(());
+            }
+            for path in event.paths {
+                // The files of a new directory may be created before it is watched
+                if matches!(event.kind, EventKind::Create(_))
+                    && path.is_dir()
+                    && !is_excluded(&path)
+                {
+                    changed.extend(watcher.add_tree(&path, &is_excluded)?);
+                }
+                changed.insert(path);
+            }
+            Ok(())
+        };
+
+        add(event)?;
+        while let Ok(event) = receiver.recv_timeout(DEBOUNCE) {
+            add(event)?;
+        }
+
+        if !changed.is_empty() {
+            on_change(changed.into_iter().collect());
+        }
+    }
+
+    Ok(())
+}
+
+struct Watches<W> {
+    watcher: W,
+    /// Directories that are watched, to watch each of them only once
+    watched: BTreeSet<PathBuf>,
+}
+
+impl<W: Watcher> Watches<W> {
+    fn add(&mut self, dir: &Path) -> Result<(), WatchError> {
+        let dir = std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+        if self.watched.insert(dir.clone()) {
+            self.watcher
+                .watch(&dir, RecursiveMode::NonRecursive)
+                .map_err(|source| WatchError::Watch { path: dir, source })?;
+        }
+        Ok(())
+    }
+
+    /// Watch `dir` and its subdirectories that are not excluded, returns the files in them.
+    fn add_tree(
+        &mut self,
+        dir: &Path,
+        is_excluded: &impl Fn(&Path) -> bool,
+    ) -> Result<Vec<PathBuf>, WatchError> {
+        let dir = std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+        let mut files = Vec::new();
+        let entries = WalkDir::new(&dir)
+            .into_iter()
+            .filter_entry(|e| e.depth() == 0 || !is_excluded(e.path()))
+            .filter_map(|e| e.ok());
+        for entry in entries {
+            if entry.file_type().is_dir() {
+                self.add(entry.path())?;
+            } else {
+                files.push(entry.into_path());
+            }
+        }
+        Ok(files)
+    }
+}